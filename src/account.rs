@@ -1,5 +1,15 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
 use serde_derive::{Serialize, Deserialize};
-use restson::{Error as RestsonError,RestPath};
+use serde::{de, ser};
+
+use crate::Error;
+use crate::client::RestPath;
+use crate::country::Country;
+use crate::courier::Courier;
+use crate::money::Amount;
 
 /// Enum describing possible authentication objects
 ///
@@ -13,17 +23,72 @@ pub enum AuthenticateWith<'a> {
     APIKey(&'a str),
 }
 
-/// Currently authenticated member
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Member {
-    id: u8,
-    company_name: String,
-    postcode: u8,
-    active: bool,
+/// The profile of the authenticated member
+/// 
+/// Fetched when a client authenticates and kept on the client; see
+/// `Client::member`. Balances are read-only, and are ignored when the
+/// profile is updated.
+/// 
+/// As defined by the [specification](https://transdirectapiv4.docs.apiary.io/reference/member)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Member<M = f64> where M: Amount {
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u32>, // The user logged in, where the member has several
+    #[serde(default)]
+    pub company_name: String,
+    #[serde(default)]
+    pub contact_name: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub abn: String,
+    #[serde(default, deserialize_with = "crate::locations::deserialize_postcode")]
+    pub postcode: String,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_address: Option<Account>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_sender: Option<Account>, // Used when a booking or order has no sender
+    #[serde(default)]
+    pub balance: M, // Prepaid credit remaining
+    #[serde(default)]
+    pub credit_limit: M,
+    #[serde(default)]
+    pub settings: MemberSettings,
 }
 
-impl RestPath<()> for Member {
-    fn get_path(_: ()) -> Result<String, RestsonError> { Ok(String::from("member")) }
+impl<M> RestPath<()> for Member<M> where M: Amount {
+    fn get_path(_: ()) -> Result<String, Error> { Ok(String::from("member")) }
+}
+
+impl<M> Member<M> where M: Amount {
+    /// The credit available for bookings, i.e. the balance plus the credit
+    /// limit, as a float
+    pub fn available_credit(&self) -> f64 {
+        self.balance.as_f64() + self.credit_limit.as_f64()
+    }
+}
+
+/// The member's booking preferences
+/// 
+/// Settings not modelled here are kept in `other`, so that updating the
+/// profile does not reset them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct MemberSettings {
+    #[serde(default)]
+    pub insure_by_default: bool,
+    #[serde(default)]
+    pub tailgate_pickup: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_courier: Option<Courier>,
+    #[serde(default)]
+    pub notifications: HashMap<String, bool>, // e.g. "booking_confirmed" => true
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 /// A user account (sender or receiver)
@@ -35,10 +100,225 @@ pub struct Account {
     pub email: String,
     pub name: String,
     pub postcode: String,
-    pub state: String,
+    pub state: AustralianState,
     pub suburb: String,
-    #[serde(alias = "type")]
-    pub kind: String, // "type" is a keyword
-    pub country: String, // two-letter ISO country code
+    #[serde(rename = "type", alias = "kind")]
+    pub kind: AccountKind, // "type" is a keyword
+    pub country: Country,
     pub company_name: String,
+}
+
+/// Enum describing whether an address is a home or a business
+/// 
+/// User input is parsed with [`str::parse`], ignoring case and surrounding
+/// whitespace, and rejected if it is neither. Anything else the API returns
+/// is kept verbatim in `Other`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub enum AccountKind {
+    #[default]
+    Residential,
+    Business,
+    Other(String),
+}
+
+impl AccountKind {
+    /// The value used for this kind by the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Residential => "residential",
+            Self::Business    => "business",
+            Self::Other(kind) => kind,
+        }
+    }
+}
+
+impl FromStr for AccountKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "residential" | "residence" | "home" => Ok(Self::Residential),
+            "business" | "commercial" | "company" => Ok(Self::Business),
+            other => Err(Error::Unrecognised(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for AccountKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for AccountKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let kind = String::deserialize(deserializer)?;
+        Ok(kind.parse().unwrap_or(Self::Other(kind)))
+    }
+}
+
+/// Enum describing the Australian states and territories
+/// 
+/// Sent as their abbreviations (e.g. `"NSW"`). Regions outside Australia,
+/// and anything else the API returns, are kept verbatim in `Other`; the
+/// default is an empty `Other`, i.e. no state. User input can be parsed
+/// leniently with [`str::parse`], which accepts abbreviations and full
+/// names in any case.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::AustralianState;
+/// 
+/// assert_eq!("Western Australia".parse::<AustralianState>().unwrap(), AustralianState::WesternAustralia);
+/// assert_eq!("vic.".parse::<AustralianState>().unwrap(), AustralianState::Victoria);
+/// assert_eq!(AustralianState::from_postcode("2600"), Some(AustralianState::AustralianCapitalTerritory));
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AustralianState {
+    AustralianCapitalTerritory,
+    NewSouthWales,
+    NorthernTerritory,
+    Queensland,
+    SouthAustralia,
+    Tasmania,
+    Victoria,
+    WesternAustralia,
+    Other(String),
+}
+
+impl Default for AustralianState {
+    fn default() -> Self {
+        Self::Other(String::new())
+    }
+}
+
+impl AustralianState {
+    /// The abbreviation used for this state by the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::AustralianCapitalTerritory => "ACT",
+            Self::NewSouthWales              => "NSW",
+            Self::NorthernTerritory          => "NT",
+            Self::Queensland                 => "QLD",
+            Self::SouthAustralia             => "SA",
+            Self::Tasmania                   => "TAS",
+            Self::Victoria                   => "VIC",
+            Self::WesternAustralia           => "WA",
+            Self::Other(state)               => state,
+        }
+    }
+
+    /// The state an Australian postcode belongs to, as allocated by
+    /// Australia Post
+    pub fn from_postcode(postcode: &str) -> Option<Self> {
+        let postcode = postcode.trim();
+        if postcode.len() != 4 || !postcode.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let state = match postcode.parse::<u16>().ok()? {
+            200..=299 | 2600..=2618 | 2900..=2920 => Self::AustralianCapitalTerritory,
+            800..=999                             => Self::NorthernTerritory,
+            1000..=2599 | 2619..=2899 | 2921..=2999 => Self::NewSouthWales,
+            3000..=3999 | 8000..=8999             => Self::Victoria,
+            4000..=4999 | 9000..=9999             => Self::Queensland,
+            5000..=5999                           => Self::SouthAustralia,
+            6000..=6999                           => Self::WesternAustralia,
+            7000..=7999                           => Self::Tasmania,
+            _ => return None,
+        };
+        Some(state)
+    }
+
+    /// Every state an address with this postcode may be in, starting with
+    /// the one it is allocated to
+    /// 
+    /// Some towns near a border use a postcode from across it, e.g.
+    /// Queanbeyan's 2620 also covers Hume in the ACT, and Barooga in NSW
+    /// uses the Victorian 3644.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::AustralianState;
+    /// 
+    /// let states = AustralianState::postcode_states("2620");
+    /// assert_eq!(states, [AustralianState::NewSouthWales, AustralianState::AustralianCapitalTerritory]);
+    /// assert_eq!(AustralianState::postcode_states("3000"), [AustralianState::Victoria]);
+    /// ```
+    pub fn postcode_states(postcode: &str) -> Vec<Self> {
+        let Some(state) = Self::from_postcode(postcode) else {
+            return Vec::new();
+        };
+
+        let across: &[Self] = match postcode.trim().parse::<u16>() {
+            Ok(872)                       => &[Self::SouthAustralia, Self::WesternAustralia],
+            Ok(2406)                      => &[Self::Queensland],
+            Ok(2540 | 2620)               => &[Self::AustralianCapitalTerritory],
+            Ok(3585 | 3644 | 3691 | 3707) => &[Self::NewSouthWales],
+            Ok(4375..=4385)               => &[Self::NewSouthWales],
+            _ => &[],
+        };
+        std::iter::once(state).chain(across.iter().cloned()).collect()
+    }
+
+    /// Whether this is one of the eight states and territories
+    pub fn is_australian(&self) -> bool {
+        !matches!(self, Self::Other(_))
+    }
+}
+
+impl FromStr for AustralianState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase().replace('.', "");
+        match s.split_whitespace().collect::<Vec<_>>().join(" ").as_str() {
+            "act" | "australian capital territory" => Ok(Self::AustralianCapitalTerritory),
+            "nsw" | "new south wales"              => Ok(Self::NewSouthWales),
+            "nt" | "northern territory"            => Ok(Self::NorthernTerritory),
+            "qld" | "queensland"                   => Ok(Self::Queensland),
+            "sa" | "south australia"               => Ok(Self::SouthAustralia),
+            "tas" | "tasmania"                     => Ok(Self::Tasmania),
+            "vic" | "victoria"                     => Ok(Self::Victoria),
+            "wa" | "western australia"             => Ok(Self::WesternAustralia),
+            _ => Err(Error::Unrecognised(s)),
+        }
+    }
+}
+
+impl fmt::Display for AustralianState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for AustralianState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for AustralianState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let state = String::deserialize(deserializer)?;
+        Ok(state.parse().unwrap_or(Self::Other(state)))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,390 @@
+use base64::Engine;
+use num_traits::{Float,Unsigned};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use tracing::Instrument;
+use url::Url;
+
+use crate::Error;
+use crate::retry::RetryPolicy;
+use crate::logging::ApiCall;
+use crate::transport::{AsyncTransport,Request,Response};
+use crate::money::Amount;
+use crate::account::{Account,AuthenticateWith,Member};
+use crate::courier::Courier;
+use crate::booking::{BookingConfirmation,BookingQuery,BookingRequest,BookingResponse};
+use crate::order::{Order,OrderBulk,OrderGroup,OrderQuery,OrderStatus};
+use crate::tracking::{TrackingEvent,TrackingGroup,TrackingReference};
+use crate::locations::{Location,LocationGroup};
+use crate::client::{legacy_query,path,ApiRequest,BookingResponseGroup,ClientBuilder,Pager};
+
+/// Asynchronous client object for interacting with the API
+/// 
+/// Mirrors [`crate::client::Client`] method for method, but every call
+/// returns a future instead of blocking the current thread. Requests are sent
+/// through an [`AsyncTransport`]; the default uses `hyper`, so the futures
+/// must be awaited from within a tokio runtime.
+/// 
+/// Calls are traced in the same way as the blocking client's; see
+/// [`crate::logging`].
+/// 
+/// Only available with the `async` feature enabled. Document downloads
+/// (labels and the like) are only offered by the blocking client.
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// # async fn run() -> Result<(), transdirect::Error> {
+/// use transdirect::TransdirectAsyncClient as AsyncClient;
+/// 
+/// let c = AsyncClient::from_api_key("my-api-key").await?;
+/// let booking: transdirect::BookingResponse = c.booking(623630).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncClient<'a> {
+    authenticated: bool,
+    transport: Arc<dyn AsyncTransport>,
+    base_url: Url,
+    headers: Vec<(&'static str, String)>, // Including credentials
+    retry: RetryPolicy,
+    log_bodies: bool,
+    member: Option<Member>, // As of authentication or the last update
+    pub sender: Option<&'a Account>, // Should eventually be default
+}
+
+impl<'a> AsyncClient<'a> {
+    pub fn new() -> Self {
+        ClientBuilder::new()
+            .build_async()
+            .expect("Should be a valid URL or connected to the internet")
+    }
+
+    pub fn builder() -> ClientBuilder<'a> {
+        ClientBuilder::new()
+    }
+
+    pub(crate) fn from_parts(
+        transport: Arc<dyn AsyncTransport>,
+        base_url: Url,
+        headers: Vec<(&'static str, String)>,
+        retry: RetryPolicy,
+        log_bodies: bool,
+        sender: Option<&'a Account>,
+    ) -> Self {
+        Self {
+            authenticated: false,
+            transport,
+            base_url,
+            headers,
+            retry,
+            log_bodies,
+            member: None,
+            sender,
+        }
+    }
+    
+    pub async fn from_auth(auth: AuthenticateWith<'_>) -> Result<Self, Error> {
+        let mut newclient = Self::new();
+        
+        newclient.auth(auth).await?;
+
+        Ok(newclient)
+    }
+    
+    pub async fn from_basic(user: &str, password: &str) -> Result<Self, Error> {
+        Self::from_auth(AuthenticateWith::Basic(user, password)).await
+    }
+    
+    pub async fn from_api_key(apikey: &str) -> Result<Self, Error> {
+        Self::from_auth(AuthenticateWith::APIKey(apikey)).await
+    }
+    
+    pub async fn auth(&mut self, auth: AuthenticateWith<'_>) -> Result<(), Error> {
+        use AuthenticateWith::*;
+
+        self.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Authorization") && !name.eq_ignore_ascii_case("Api-key"));
+
+        match auth {
+            Basic(user, pass) => {
+                let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+                self.headers.push(("Authorization", format!("Basic {credentials}")));
+            },
+            APIKey(key) => self.headers.push(("Api-key", key.to_string())),
+        }
+        
+        self.refresh_member::<f64>().await?;
+        self.authenticated = true;
+
+        Ok(())
+    }
+
+    /// The profile of the authenticated member, as of authentication or the
+    /// last call to [`AsyncClient::refresh_member`] or [`AsyncClient::update_member`]
+    pub fn member(&self) -> Option<&Member> {
+        self.member.as_ref()
+    }
+
+    /// Fetches the member's profile again, e.g. for an up-to-date balance
+    pub async fn refresh_member<M>(&mut self) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned {
+        let member = self.execute::<Value>(ApiRequest::get(path::<Member, _>(())?)).await?;
+        self.keep_member(member)
+    }
+
+    /// Updates the member's profile, returning it as updated by the server
+    pub async fn update_member<M>(&mut self, member: &Member<M>) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned + Serialize {
+        let member = self.execute::<Value>(ApiRequest::put(path::<Member<M>, _>(())?).body(member)?).await?;
+        self.keep_member(member)
+    }
+
+    // Keeps the profile returned by the server, and reads it as `Member<M>`
+    fn keep_member<M>(&mut self, member: Value) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned {
+        self.member = Some(serde_json::from_value(member.clone()).map_err(|e| Error::Transport(Box::new(e)))?);
+        serde_json::from_value(member).map_err(|e| Error::Transport(Box::new(e)))
+    }
+    
+    pub async fn quotes<T, U, M>(&self, request: &BookingRequest<'_, T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::post(path::<BookingRequest<T, U, M>, _>(())?)
+            .body(request)?
+            .idempotent(self.retry.retry_quotes)).await
+    }
+    
+    /// Gets a copy of a booking from its id; see [`crate::client::Client::booking`]
+    pub async fn booking<T, U, M>(&self, booking_id: u32) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<BookingResponse<T, U, M>, _>(booking_id)?).booking(booking_id)).await
+    }
+    
+    pub async fn update_booking<T, U, M>(&self, booking_id: u32, request: &BookingRequest<'_, T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::put(path::<BookingRequest<T, U, M>, _>(booking_id)?)
+            .body(request)?
+            .booking(booking_id)).await
+    }
+
+    pub async fn cancel_booking<T, U, M>(&self, booking_id: u32) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute::<()>(ApiRequest::delete(path::<BookingResponse<T, U, M>, _>(booking_id)?).booking(booking_id)).await?;
+
+        self.booking(booking_id).await
+    }
+
+    pub async fn confirm_booking<T, U, M>(&self, booking_id: u32, courier: &Courier, pickup_date: time::Date) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        let confirmation = BookingConfirmation {
+            courier: courier.clone(),
+            pickup_date,
+        };
+
+        self.execute::<()>(ApiRequest::post(path::<BookingConfirmation, _>(booking_id)?)
+            .body(&confirmation)?
+            .booking(booking_id)
+            .discard_response()).await?;
+
+        self.booking(booking_id).await
+    }
+    
+    /// Lists the bookings matching `query`, fetching a page each time
+    /// [`AsyncBookings::next_page`] is awaited
+    /// 
+    /// Pages run out in the same way as `Client::bookings`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # async fn run(c: transdirect::TransdirectAsyncClient<'_>) -> Result<(), transdirect::Error> {
+    /// use transdirect::{BookingQuery, BookingResponse};
+    /// 
+    /// let mut pages = c.bookings(&BookingQuery::new().per_page(100));
+    /// while let Some(page) = pages.next_page().await {
+    ///     for booking in page? {
+    ///         let booking: BookingResponse = booking;
+    ///         println!("{}: {:?}", booking.id, booking.connote);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bookings<'c, T, U, M>(&'c self, query: &BookingQuery) -> AsyncBookings<'c, 'a, T, U, M>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        AsyncBookings {
+            client: self,
+            pager: Pager::new(query),
+            bookings: PhantomData,
+        }
+    }
+
+    /// See `Client::bookings_page`
+    pub async fn bookings_page<T, U, M>(&self, query: &BookingQuery) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.bookings_group(query).await.map(BookingResponseGroup::into_bookings)
+    }
+
+    async fn bookings_group<T, U, M>(&self, query: &BookingQuery) -> Result<BookingResponseGroup<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<BookingResponseGroup<T, U, M>, _>(())?).query(query.to_pairs())).await
+    }
+
+    /// Every booking matching `query`, from its page onwards
+    pub async fn all_bookings<T, U, M>(&self, query: &BookingQuery) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        let mut pages = self.bookings(query);
+        let mut bookings = Vec::new();
+
+        while let Some(page) = pages.next_page().await {
+            bookings.extend(page?);
+        }
+        Ok(bookings)
+    }
+    
+    pub async fn bookings_after_date<T, U, M>(&self, date: time::OffsetDateTime)
+    -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.bookings_after_date_sort_by(date, "").await
+    }
+
+    pub async fn bookings_sort_by<T, U, M>(&self, field: &str) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.bookings_after_date_sort_by(time::OffsetDateTime::UNIX_EPOCH, field).await
+    }
+
+    pub async fn bookings_after_date_sort_by<T, U, M>(&self, date: time::OffsetDateTime, field: &str) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.all_bookings(&legacy_query(date, field)).await
+    }
+
+    pub async fn create_order<T, U, M>(&self, order: &Order<T, U, M>) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::post(path::<Order<T, U, M>, _>(())?).body(order)?).await
+    }
+
+    pub async fn create_orders<T, U, M>(&self, orders: &[Order<T, U, M>]) -> Result<Vec<Order<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute::<OrderGroup<T, U, M>>(ApiRequest::post(path::<OrderBulk<T, U, M>, _>(())?).body(&OrderBulk(orders))?).await
+            .map(|group| group.0)
+    }
+
+    pub async fn orders<T, U, M>(&self, query: &OrderQuery) -> Result<Vec<Order<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute::<OrderGroup<T, U, M>>(ApiRequest::get(path::<OrderGroup<T, U, M>, _>(())?).query(query.to_pairs())).await
+            .map(|group| group.0)
+    }
+
+    pub async fn order<T, U, M>(&self, order_id: u32) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<Order<T, U, M>, _>(order_id)?)).await
+    }
+
+    pub async fn update_order<T, U, M>(&self, order_id: u32, order: &Order<T, U, M>) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::put(path::<Order<T, U, M>, _>(order_id)?).body(order)?).await
+    }
+
+    pub async fn delete_order(&self, order_id: u32) -> Result<(), Error> {
+        self.execute(ApiRequest::delete(path::<Order<u32, f64>, _>(order_id)?)).await // Parameters only pick the path
+    }
+
+    pub async fn quote_order<T, U, M>(&self, order: &Order<T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + Clone + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.quotes(&BookingRequest::from(order)).await
+    }
+
+    /// Quotes and books an order; see [`crate::client::Client::book_order`]
+    pub async fn book_order<T, U, M>(&self, order: &mut Order<T, U, M>, courier: &Courier, pickup_date: time::Date) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + Clone + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        let quote = self.quote_order(order).await?;
+        let booking = self.confirm_booking::<T, U, M>(quote.id, courier, pickup_date).await?;
+
+        if booking.is_confirmed() {
+            order.status = OrderStatus::Booked;
+            order.selected_courier = Some(courier.clone());
+            order.connote = booking.connote.clone();
+
+            if let Some(id) = order.id {
+                *order = self.update_order(id, order).await?;
+            }
+        }
+
+        Ok(booking)
+    }
+
+    pub async fn tracking<'r>(&self, reference: impl Into<TrackingReference<'r>>) -> Result<Vec<TrackingEvent>, Error> {
+        let reference = reference.into();
+        let booking_id = match reference {
+            TrackingReference::Booking(id) => Some(id),
+            _ => None,
+        };
+        let request = ApiRequest::get(path::<TrackingGroup, _>(reference)?);
+
+        self.execute::<TrackingGroup>(ApiRequest { booking_id, ..request }).await
+            .map(TrackingGroup::into_events)
+    }
+
+    pub async fn locations(&self, query: &str) -> Result<Vec<Location>, Error> {
+        self.execute::<LocationGroup>(ApiRequest::get(path::<LocationGroup, _>(())?).query(vec![("q", query.trim().to_string())])).await
+            .map(LocationGroup::into_locations)
+    }
+
+    // See `Client::execute`
+    async fn execute<R>(&self, request: ApiRequest) -> Result<R, Error>
+    where R: DeserializeOwned {
+        let call = ApiCall::new(request.method.as_str(), &request.path, self.log_bodies).booking(request.booking_id);
+        let response = self.send(call, &request.to_http(&self.base_url, &self.headers)?, self.retry.permits(request.method, request.idempotent)).await?;
+
+        request.read(&response)
+    }
+
+    async fn send(&self, call: ApiCall, request: &Request, idempotent: bool) -> Result<Response, Error> {
+        let call = call.request(request);
+        let response = self.retry
+            .run_async(idempotent, move || async move { self.transport.send(request).await?.error_for_status() })
+            .instrument(call.span().clone())
+            .await;
+        call.finish(response.as_ref());
+
+        response
+    }
+}
+
+impl Default for AsyncClient<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The bookings matching a [`BookingQuery`], fetched a page at a time; see
+/// [`AsyncClient::bookings`]
+pub struct AsyncBookings<'c, 'a, T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    client: &'c AsyncClient<'a>,
+    pager: Pager,
+    bookings: PhantomData<BookingResponse<T, U, M>>,
+}
+
+impl<T, U, M> AsyncBookings<'_, '_, T, U, M>
+where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+    /// Fetches the next page, or `None` once the last page or an error has
+    /// been returned
+    pub async fn next_page(&mut self) -> Option<Result<Vec<BookingResponse<T, U, M>>, Error>> {
+        let query = self.pager.next_query()?;
+
+        match self.client.bookings_group(query).await {
+            Ok(group) => {
+                let page = self.pager.turn(group);
+                (!page.is_empty()).then_some(Ok(page))
+            },
+            Err(err) => {
+                self.pager.fail();
+                Some(Err(err))
+            },
+        }
+    }
+}
@@ -1,18 +1,25 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
 
-use restson::{RestPath, Error as RestsonError};
 use num_traits::{Float,Unsigned};
 use serde_derive::{Serialize, Deserialize};
 use serde::{de, ser};
 
 use crate::product::{Product,Service};
 use crate::account::Account;
+use crate::courier::Courier;
+use crate::money::Amount;
+use crate::ranking::{self, Ranker};
+use crate::Error;
+use crate::client::RestPath;
 
 /// Enum describing the status of a booking
 /// 
 /// As defined by the [specification](https://transdirectapiv4.docs.apiary.io/reference/bookings-/-simple-quotes/single-booking)
 #[non_exhaustive]
-#[derive(Debug, Eq, PartialEq, Default)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
 pub enum BookingStatus {
     #[default]
     New,
@@ -48,13 +55,222 @@ impl<'de> de::Deserialize<'de> for BookingStatus {
     }
 }
 
+impl BookingStatus {
+    /// The value used for this status by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::New            => "new",
+            Self::PendingPayment => "pending_payment",
+            Self::Paid           => "paid",
+            Self::RequestSent    => "request_sent",
+            Self::Reviewed       => "reviewed",
+            Self::Confirmed      => "confirmed",
+            Self::Cancelled      => "cancelled",
+            Self::PendingReview  => "pending_review",
+            Self::RequestFailed  => "request_failed",
+            Self::BookedManually => "booked_manually",
+        }
+    }
+
+    /// Whether the booking has gone through to a courier, i.e. it has been
+    /// confirmed and has not since been cancelled or failed
+    /// 
+    /// Bookings still awaiting payment or review are not confirmed, so
+    /// labels should not be printed for them yet.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::BookingStatus;
+    /// 
+    /// assert!(BookingStatus::Confirmed.is_confirmed());
+    /// assert!(!BookingStatus::PendingPayment.is_confirmed());
+    /// assert!(!BookingStatus::PendingReview.is_confirmed());
+    /// ```
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self,
+            Self::Paid | Self::RequestSent | Self::Reviewed | Self::Confirmed | Self::BookedManually)
+    }
+}
+
+/// The fields bookings can be listed in order of
+/// 
+/// Any other field the API sorts by can be sent verbatim as `Other`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum BookingSort {
+    BookedAt,
+    CreatedAt,
+    UpdatedAt,
+    Status,
+    Other(String),
+}
+
+impl BookingSort {
+    /// The value used for this field by the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::BookedAt     => "booking_time",
+            Self::CreatedAt    => "created_at",
+            Self::UpdatedAt    => "updated_at",
+            Self::Status       => "status",
+            Self::Other(field) => field,
+        }
+    }
+}
+
+impl FromStr for BookingSort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "booking_time" | "booked_at" => Ok(Self::BookedAt),
+            "created_at"                 => Ok(Self::CreatedAt),
+            "updated_at"                 => Ok(Self::UpdatedAt),
+            "status"                     => Ok(Self::Status),
+            _ => Err(Error::Unrecognised(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    /// The value used for this direction by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ascending  => "asc",
+            Self::Descending => "desc",
+        }
+    }
+}
+
+/// The number of bookings fetched at a time, unless set with
+/// [`BookingQuery::per_page`]
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Filters and orders a listing of bookings
+/// 
+/// Every filter is optional. Pages are numbered from 1, and are walked
+/// automatically by `Client::bookings`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::{BookingQuery, BookingStatus};
+/// use transdirect::booking::{BookingSort, SortDirection};
+/// 
+/// let since = time::Date::from_calendar_date(2023, time::Month::January, 1).unwrap().midnight().assume_utc();
+/// let query = BookingQuery::new()
+///     .since(since)
+///     .until(since + time::Duration::days(365))
+///     .status(BookingStatus::Confirmed)
+///     .sort(BookingSort::BookedAt, SortDirection::Descending)
+///     .per_page(100);
+/// 
+/// assert_eq!(query.to_pairs()[0], ("since", "2023-01-01T00:00:00.000000000Z".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BookingQuery {
+    since: Option<time::OffsetDateTime>,
+    until: Option<time::OffsetDateTime>,
+    status: Option<BookingStatus>,
+    sort: Option<(BookingSort, SortDirection)>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+impl BookingQuery {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Only bookings made at or after `since`
+    pub fn since(mut self, since: time::OffsetDateTime) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only bookings made before `until`
+    pub fn until(mut self, until: time::OffsetDateTime) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn status(mut self, status: BookingStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn sort(mut self, field: BookingSort, direction: SortDirection) -> Self {
+        self.sort = Some((field, direction));
+        self
+    }
+
+    /// Starts from page `page`, counting from 1
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = Some(page.max(1));
+        self
+    }
+
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        self.per_page = Some(per_page.max(1));
+        self
+    }
+
+    /// The same query for the following page
+    pub fn next_page(self) -> Self {
+        let page = self.page_number() + 1;
+        self.page(page)
+    }
+
+    pub fn page_number(&self) -> u32 {
+        self.page.unwrap_or(1)
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.per_page.unwrap_or(DEFAULT_PAGE_SIZE)
+    }
+
+    /// The `(key, value)` pairs of the query string
+    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let format = |date: time::OffsetDateTime| date
+            .format(&time::format_description::well_known::Iso8601::DEFAULT)
+            .expect("Any OffsetDateTime should be formattable as ISO-8601");
+        let mut pairs = Vec::new();
+
+        if let Some(since) = self.since {
+            pairs.push(("since", format(since)));
+        }
+        if let Some(until) = self.until {
+            pairs.push(("until", format(until)));
+        }
+        if let Some(status) = self.status {
+            pairs.push(("status", status.as_str().to_string()));
+        }
+        if let Some((field, direction)) = &self.sort {
+            pairs.push(("sort", field.as_str().to_string()));
+            pairs.push(("direction", direction.as_str().to_string()));
+        }
+        pairs.push(("page", self.page_number().to_string()));
+        pairs.push(("per_page", self.page_size().to_string()));
+
+        pairs
+    }
+}
+
 /// Represents a single booking request (quote or order)
 /// 
 /// 
 #[derive(Debug, Serialize, Default)]
-pub struct BookingRequest<'a, T, U>
-where T: Unsigned + ser::Serialize, U: Float + ser::Serialize {
-    pub declared_value: U,
+pub struct BookingRequest<'a, T, U, M = U>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    pub declared_value: M,
     pub referrer: String,
     pub requesting_site: String,
     pub tailgate_pickup: bool,
@@ -64,8 +280,8 @@ where T: Unsigned + ser::Serialize, U: Float + ser::Serialize {
     pub receiver: Option<&'a Account>,
 }
 
-impl<'a, T, U> BookingRequest<'a, T, U>
-where T: Unsigned + ser::Serialize + Default, U: Float + ser::Serialize + Default {
+impl<'a, T, U, M> BookingRequest<'a, T, U, M>
+where T: Unsigned + ser::Serialize + Default, U: Float + ser::Serialize + Default, M: Amount + ser::Serialize {
     /// Creates an empty `BookingRequest`
     /// 
     /// Each element will be either empty, 0, or false.
@@ -94,25 +310,226 @@ where T: Unsigned + ser::Serialize + Default, U: Float + ser::Serialize + Defaul
     }
 }
 
-impl<T, U> RestPath<()> for BookingRequest<'_, T, U>
-where T: Unsigned + ser::Serialize, U: Float + ser::Serialize {
-    fn get_path(_: ()) -> Result<String, RestsonError> { Ok("bookings/v4".to_string()) }
+impl<'a, T, U, M> BookingRequest<'a, T, U, M>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    /// Starts a [`BookingRequestBuilder`], which refuses to build a request
+    /// Transdirect would reject for lack of a sender, receiver or items
+    pub fn builder() -> BookingRequestBuilder<'a, T, U, M> {
+        BookingRequestBuilder::new()
+    }
+}
+
+/// The `referrer` sent unless another is set on the builder
+pub const DEFAULT_REFERRER: &str = "API";
+
+/// Builds a [`BookingRequest`] with a sender, a receiver and at least one
+/// item
+///
+/// Unlike [`BookingRequest::new`], `referrer` defaults to
+/// [`DEFAULT_REFERRER`]. `requesting_site` is left empty unless set.
+///
+/// # Examples
+///
+/// ```
+/// use transdirect::{Account, BookingRequest, Product};
+///
+/// let warehouse = Account { postcode: "2000".to_string(), ..Account::default() };
+/// let customer = Account { postcode: "3000".to_string(), ..Account::default() };
+///
+/// let request = BookingRequest::builder()
+///     .sender(&warehouse)
+///     .receiver(&customer)
+///     .item(Product::new())
+///     .declared_value(120.0)
+///     .build()
+///     .expect("Complete request");
+///
+/// let incomplete = BookingRequest::builder().sender(&warehouse).build();
+/// assert_eq!(incomplete.unwrap_err().to_string(), "booking request is missing: receiver, items");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BookingRequestBuilder<'a, T, U, M = U>
+where T: Unsigned, U: Float, M: Amount {
+    declared_value: M,
+    referrer: String,
+    requesting_site: String,
+    tailgate_pickup: bool,
+    tailgate_delivery: bool,
+    items: Vec<Product<T, U>>,
+    sender: Option<&'a Account>,
+    receiver: Option<&'a Account>,
+}
+
+impl<'a, T, U, M> BookingRequestBuilder<'a, T, U, M>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    pub fn new() -> Self {
+        Self {
+            declared_value: M::default(),
+            referrer: DEFAULT_REFERRER.to_string(),
+            requesting_site: String::new(),
+            tailgate_pickup: false,
+            tailgate_delivery: false,
+            items: Vec::new(),
+            sender: None,
+            receiver: None,
+        }
+    }
+
+    pub fn sender(mut self, sender: &'a Account) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn receiver(mut self, receiver: &'a Account) -> Self {
+        self.receiver = Some(receiver);
+        self
+    }
+
+    pub fn item(mut self, item: Product<T, U>) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn items(mut self, items: impl IntoIterator<Item = Product<T, U>>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    pub fn declared_value(mut self, declared_value: M) -> Self {
+        self.declared_value = declared_value;
+        self
+    }
+
+    pub fn tailgate_pickup(mut self, tailgate_pickup: bool) -> Self {
+        self.tailgate_pickup = tailgate_pickup;
+        self
+    }
+
+    pub fn tailgate_delivery(mut self, tailgate_delivery: bool) -> Self {
+        self.tailgate_delivery = tailgate_delivery;
+        self
+    }
+
+    pub fn referrer(mut self, referrer: &str) -> Self {
+        self.referrer = referrer.to_string();
+        self
+    }
+
+    pub fn requesting_site(mut self, requesting_site: &str) -> Self {
+        self.requesting_site = requesting_site.to_string();
+        self
+    }
+
+    /// Fails with [`BuildError::Missing`], naming every missing field,
+    /// unless a sender, a receiver and at least one item were given
+    pub fn build(self) -> Result<BookingRequest<'a, T, U, M>, BuildError> {
+        let missing: Vec<&'static str> = [
+            ("sender", self.sender.is_none()),
+            ("receiver", self.receiver.is_none()),
+            ("items", self.items.is_empty()),
+        ]
+            .into_iter()
+            .filter_map(|(field, missing)| missing.then_some(field))
+            .collect();
+        if !missing.is_empty() {
+            return Err(BuildError::Missing(missing));
+        }
+
+        Ok(BookingRequest {
+            declared_value: self.declared_value,
+            referrer: self.referrer,
+            requesting_site: self.requesting_site,
+            tailgate_pickup: self.tailgate_pickup,
+            tailgate_delivery: self.tailgate_delivery,
+            items: self.items,
+            sender: self.sender,
+            receiver: self.receiver,
+        })
+    }
+}
+
+/// Why a [`BookingRequestBuilder`] could not build a request
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    Missing(Vec<&'static str>), // Fields a request cannot be sent without
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(fields) => write!(f, "booking request is missing: {}", fields.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl<T, U, M> Default for BookingRequestBuilder<'_, T, U, M>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, M> RestPath<()> for BookingRequest<'_, T, U, M>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("bookings/v4".to_string()) }
+}
+
+// Updating an existing booking
+impl<T, U, M> RestPath<u32> for BookingRequest<'_, T, U, M>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    fn get_path(params: u32) -> Result<String, Error> {
+        Ok(format!("bookings/v4/{params}"))
+    }
+}
+
+/// Selects the courier and pickup date for a quoted booking, confirming it
+/// 
+/// As defined by the [specification](https://transdirectapiv4.docs.apiary.io/reference/bookings-/-simple-quotes/confirm-booking)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookingConfirmation {
+    pub courier: Courier,
+    #[serde(rename = "pickup-date", serialize_with = "serialize_date")]
+    pub pickup_date: time::Date,
+}
+
+impl RestPath<u32> for BookingConfirmation {
+    fn get_path(params: u32) -> Result<String, Error> {
+        Ok(format!("bookings/v4/{params}/confirm"))
+    }
+}
+
+// Transdirect expects plain `YYYY-MM-DD` dates
+fn serialize_date<S>(date: &time::Date, serializer: S) -> Result<S::Ok, S::Error>
+where S: ser::Serializer
+{
+    serializer.serialize_str(&format!("{:04}-{:02}-{:02}", date.year(), u8::from(date.month()), date.day()))
 }
 
 // I don't know how to implement generically without running into collisions
-impl<T, U> RestPath<u32> for BookingResponse<T, U>
-where T: Unsigned, U: Float {
-    fn get_path(params: u32) -> Result<String, RestsonError> {
+impl<T, U, M> RestPath<u32> for BookingResponse<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(params: u32) -> Result<String, Error> {
         Ok(format!("bookings/v4/{params}"))
     }
 }
 
+impl<T, U, M> BookingResponse<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    /// See [`BookingStatus::is_confirmed`]
+    pub fn is_confirmed(&self) -> bool {
+        self.status.is_confirmed()
+    }
+}
+
 /// Represents a response due to a booking request from the server
 /// 
 ///
 #[derive(Debug, Deserialize)]
-pub struct BookingResponse<T, U>
-where T: Unsigned, U: Float {
+pub struct BookingResponse<T, U, M = U>
+where T: Unsigned, U: Float, M: Amount {
     pub id: u32,
     pub status: BookingStatus,
     #[serde(with = "time::serde::iso8601")]
@@ -122,13 +539,13 @@ where T: Unsigned, U: Float {
     pub created_at: time::OffsetDateTime,
     #[serde(with = "time::serde::iso8601")]
     pub updated_at: time::OffsetDateTime,
-    pub declared_value: U,
-    pub insured_value: U,
+    pub declared_value: M,
+    pub insured_value: M,
     pub description: Option<String>,
     pub items: Vec<Product<T, U>>,
     pub label: String,
     pub notifications: HashMap<String, bool>,
-    pub quotes: HashMap<String, Service<U>>,
+    pub quotes: Quotes<M>,
     pub sender: Account,
     pub receiver: Account,
     pub pickup_window: Vec<String>, // Could be a time::OffsetDateTime
@@ -137,4 +554,87 @@ where T: Unsigned, U: Float {
     pub scanned_weight: T,
     pub special_instructions: String,
     pub tailgate_delivery: bool,
-}
\ No newline at end of file
+}
+
+/// The quotes returned for a booking, one service per courier
+/// 
+/// Dereferences to the underlying map, so a single quote can be looked up
+/// with `quotes[&Courier::Toll]` or `quotes.get(..)`.
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// use transdirect::{BookingResponse, Courier};
+/// # let quotes = transdirect::booking::Quotes::<f64>::default();
+/// 
+/// if let Some((courier, service)) = quotes.cheapest() {
+///     println!("{courier} for ${}", service.total);
+/// }
+/// let road = quotes.with_service("road").count();
+/// let toll = quotes.for_courier(&Courier::Toll);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Quotes<M>(HashMap<Courier, Service<M>>)
+where M: Amount;
+
+impl<M> Default for Quotes<M>
+where M: Amount {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<M> Quotes<M>
+where M: Amount {
+    /// The quote with the lowest total price; see [`ranking::Cheapest`]
+    pub fn cheapest(&self) -> Option<(&Courier, &Service<M>)> {
+        Ranker::new(ranking::Cheapest).best(self).map(|r| (r.courier, r.service))
+    }
+
+    /// The quote with the shortest transit time, preferring the cheaper
+    /// quote on ties. Quotes without a readable transit time are ignored;
+    /// see [`ranking::Fastest`].
+    pub fn fastest(&self) -> Option<(&Courier, &Service<M>)> {
+        Ranker::new(ranking::Fastest).best(self).map(|r| (r.courier, r.service))
+    }
+
+    pub fn for_courier(&self, courier: &Courier) -> Option<&Service<M>> {
+        self.0.get(courier)
+    }
+
+    /// The quotes whose service type (e.g. `"road"`, `"air"`) matches,
+    /// ignoring case
+    pub fn with_service<'a>(&'a self, service: &'a str) -> impl Iterator<Item = (&'a Courier, &'a Service<M>)> {
+        self.0
+            .iter()
+            .filter(move |(_, s)| s.service.eq_ignore_ascii_case(service))
+    }
+
+    pub fn into_inner(self) -> HashMap<Courier, Service<M>> {
+        self.0
+    }
+}
+
+impl<M> Deref for Quotes<M>
+where M: Amount {
+    type Target = HashMap<Courier, Service<M>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<M> From<HashMap<Courier, Service<M>>> for Quotes<M>
+where M: Amount {
+    fn from(quotes: HashMap<Courier, Service<M>>) -> Self {
+        Self(quotes)
+    }
+}
+
+impl<M> FromIterator<(Courier, Service<M>)> for Quotes<M>
+where M: Amount {
+    fn from_iter<I: IntoIterator<Item = (Courier, Service<M>)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
@@ -0,0 +1,381 @@
+//! Recording and replaying exchanges with the API
+//!
+//! A [`Cassette`] is a [`Transport`] which either records each request and
+//! response sent through another transport to a JSON file, or replays a
+//! previously recorded file without sending anything. Tests can then be run
+//! against real responses deterministically and offline.
+//!
+//! Interactions are keyed by method, path (including the query string) and
+//! a hash of the request body, and identical requests are replayed in the
+//! order they were recorded. Credentials never reach the file: request
+//! headers are not stored at all, and password, API key and token fields are
+//! redacted from bodies before they are hashed or written.
+//!
+//! Only available with the `testing` feature enabled.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use transdirect::cassette::Cassette;
+//! use transdirect::client::ClientBuilder;
+//! use transdirect::transport::UreqTransport;
+//!
+//! // Once, against the real API
+//! let c = ClientBuilder::new()
+//!     .transport(Cassette::record("tests/cassettes/booking.json", UreqTransport::new()))
+//!     .build()
+//!     .expect("Valid base URL");
+//! # let _ = c;
+//!
+//! // From then on
+//! let c = ClientBuilder::new()
+//!     .transport(Cassette::replay("tests/cassettes/booking.json").expect("Readable cassette"))
+//!     .build()
+//!     .expect("Valid base URL");
+//! let booking: transdirect::BookingResponse = c.booking(623630).expect("Recorded booking");
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use base64::Engine;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::Error;
+use crate::logging::redact_body;
+use crate::transport::{Method, Request, Response, Transport};
+
+// Response headers which are not worth keeping, or may identify a session
+const DROPPED_HEADERS: [&str; 4] = ["set-cookie", "date", "connection", "transfer-encoding"];
+
+/// A transport which records to, or replays from, a JSON file
+pub struct Cassette {
+    path: PathBuf,
+    recorder: Option<Box<dyn Transport>>, // Only when recording
+    tape: Mutex<Tape>,
+}
+
+#[derive(Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    played: HashMap<Key, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    method: Method,
+    path: String,
+    body_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>, // For reading only; matching uses the hash
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>, // Bodies which are not JSON, e.g. labels
+}
+
+impl Cassette {
+    /// Records every exchange with `transport` to `path`, replacing any
+    /// existing file. The file is rewritten after each exchange.
+    pub fn record(path: impl Into<PathBuf>, transport: impl Transport + 'static) -> Self {
+        Self {
+            path: path.into(),
+            recorder: Some(Box::new(transport)),
+            tape: Mutex::new(Tape::default()),
+        }
+    }
+
+    /// Replays the exchanges recorded in `path`
+    ///
+    /// Requests which were never recorded fail with an [`Error::Io`] of kind
+    /// `NotFound`. Once every recording of a request has been replayed, the
+    /// last one is repeated.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = std::fs::read(path).map_err(Error::Io)?;
+        let file: CassetteFile = serde_json::from_slice(&file)
+            .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            recorder: None,
+            tape: Mutex::new(Tape { interactions: file.interactions, played: HashMap::new() }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// The number of exchanges recorded or loaded
+    pub fn len(&self) -> usize {
+        self.tape().interactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn tape(&self) -> MutexGuard<'_, Tape> {
+        self.tape.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self, tape: &Tape) -> Result<(), Error> {
+        let file = CassetteFile { interactions: tape.interactions.clone() };
+        let file = serde_json::to_vec_pretty(&file).expect("Cassette should be serializable as JSON");
+
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(Error::Io)?;
+        }
+        std::fs::write(&self.path, file).map_err(Error::Io)
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("recording", &self.is_recording())
+            .field("interactions", &self.len())
+            .finish()
+    }
+}
+
+impl Transport for Cassette {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let (key, body) = key_of(request)?;
+
+        match &self.recorder {
+            Some(transport) => {
+                let response = transport.send(request)?;
+                let mut tape = self.tape();
+                tape.interactions.push(Interaction {
+                    request: RecordedRequest {
+                        method: key.method.as_str().to_string(),
+                        path: key.path,
+                        body_hash: key.body_hash,
+                        body,
+                    },
+                    response: RecordedResponse::from(&response),
+                });
+                self.save(&tape)?;
+
+                Ok(response)
+            },
+            None => {
+                let mut tape = self.tape();
+                let recordings: Vec<&Interaction> = tape.interactions
+                    .iter()
+                    .filter(|i| i.request.method == key.method.as_str()
+                        && i.request.path == key.path
+                        && i.request.body_hash == key.body_hash)
+                    .collect();
+                let Some(last) = recordings.len().checked_sub(1) else {
+                    let message = format!("no recorded interaction for {} {} in {}", key.method.as_str(), key.path, self.path.display());
+                    return Err(Error::Io(io::Error::new(io::ErrorKind::NotFound, message)));
+                };
+
+                let played = tape.played.get(&key).copied().unwrap_or_default();
+                let response = recordings[played.min(last)].response.to_response();
+                tape.played.insert(key, played + 1);
+
+                Ok(response)
+            },
+        }
+    }
+}
+
+impl From<&Response> for RecordedResponse {
+    fn from(response: &Response) -> Self {
+        let headers = response.headers
+            .iter()
+            .filter(|(name, _)| !DROPPED_HEADERS.iter().any(|d| name.eq_ignore_ascii_case(d)))
+            .cloned()
+            .collect();
+
+        let (body, body_base64) = if response.body.is_empty() {
+            (None, None)
+        } else {
+            match std::str::from_utf8(&response.body).ok().and_then(|b| serde_json::from_str::<Value>(&redact_body(b)).ok()) {
+                Some(body) => (Some(body), None),
+                None => (None, Some(base64::engine::general_purpose::STANDARD.encode(&response.body))),
+            }
+        };
+
+        Self { status: response.status, headers, body, body_base64 }
+    }
+}
+
+impl RecordedResponse {
+    fn to_response(&self) -> Response {
+        let body = match (&self.body, &self.body_base64) {
+            (Some(body), _) => serde_json::to_vec(body).expect("Recorded body should be serializable"),
+            (None, Some(body)) => base64::engine::general_purpose::STANDARD.decode(body).unwrap_or_default(),
+            (None, None) => Vec::new(),
+        };
+
+        Response { status: self.status, headers: self.headers.clone(), body }
+    }
+}
+
+// The key of a request, along with its redacted body when that is JSON
+fn key_of(request: &Request) -> Result<(Key, Option<Value>), Error> {
+    let url = Url::parse(&request.url).map_err(|e| Error::Transport(Box::new(e)))?;
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+
+    let body = request.body
+        .as_deref()
+        .and_then(|b| std::str::from_utf8(b).ok())
+        .and_then(|b| serde_json::from_str::<Value>(&redact_body(b)).ok());
+    let body_hash = match (&body, &request.body) {
+        (Some(body), _) => Some(fnv1a(&canonical(body))),
+        (None, Some(raw)) => Some(fnv1a(raw)),
+        (None, None) => None,
+    };
+
+    Ok((Key { method: request.method, path, body_hash }, body))
+}
+
+// A body's JSON with the keys of every object in order, so equal bodies
+// always hash the same however their keys were ordered
+fn canonical(body: &Value) -> Vec<u8> {
+    struct Sorted<'a>(&'a Value);
+
+    impl serde::Serialize for Sorted<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer {
+            match self.0 {
+                Value::Object(map) => serializer.collect_map(map
+                    .iter()
+                    .map(|(key, value)| (key, Sorted(value)))
+                    .collect::<BTreeMap<_, _>>()),
+                Value::Array(values) => serializer.collect_seq(values.iter().map(Sorted)),
+                value => value.serialize(serializer),
+            }
+        }
+    }
+
+    serde_json::to_vec(&Sorted(body)).expect("JSON values should be serializable")
+}
+
+// 64-bit FNV-1a, which unlike std's hashers is stable between releases
+fn fnv1a(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuthenticateWith, BookingRequest, BookingResponse, Product};
+    use crate::client::ClientBuilder;
+    use crate::testing::FakeTransdirect;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("transdirect-{}-{name}.json", std::process::id()))
+    }
+
+    #[test]
+    fn should_replay_recorded_exchanges() {
+        let path = cassette_path("replay");
+        let fake = FakeTransdirect::new();
+        let request = BookingRequest { items: vec![Product::new()], ..BookingRequest::new() };
+
+        let mut c = ClientBuilder::new()
+            .base_url(crate::testing::BASE_URL)
+            .transport(Cassette::record(&path, fake.clone()))
+            .build()
+            .unwrap();
+        c.auth(AuthenticateWith::Basic("user", "hunter2")).unwrap();
+        let recorded: BookingResponse = c.quotes(&request).unwrap();
+        let label = c.document(recorded.id, crate::DocumentKind::ConsignmentNote);
+        assert!(label.is_err()); // Recorded as a 404, since the booking is not confirmed
+
+        let c = ClientBuilder::new()
+            .base_url("http://elsewhere.test/api/")
+            .transport(Cassette::replay(&path).unwrap())
+            .build()
+            .unwrap();
+        let replayed: BookingResponse = c.quotes(&request).unwrap();
+        assert_eq!(replayed.id, recorded.id);
+        assert_eq!(c.document(recorded.id, crate::DocumentKind::ConsignmentNote).unwrap_err().status(), Some(404));
+        assert_eq!(fake.requests().len(), 3);
+
+        let file = std::fs::read_to_string(&path).unwrap();
+        assert!(!file.contains("hunter2") && !file.contains("dXNlcjpodW50ZXIy"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_on_unrecorded_requests() {
+        let path = cassette_path("empty");
+        std::fs::write(&path, r#"{ "interactions": [] }"#).unwrap();
+        let cassette = Cassette::replay(&path).unwrap();
+        let request = Request { method: Method::Get, url: "http://x.test/api/member".to_string(), headers: vec![], body: None };
+
+        match cassette.send(&request) {
+            Err(Error::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::NotFound),
+            other => panic!("Expected a miss, got {other:?}"),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_hash_bodies_regardless_of_key_order_and_credentials() {
+        let request = |body: &str| Request {
+            method: Method::Post,
+            url: "http://x.test/api/orders".to_string(),
+            headers: vec![],
+            body: Some(body.as_bytes().to_vec()),
+        };
+
+        let (a, _) = key_of(&request(r#"{"a":1,"password":"x","b":2}"#)).unwrap();
+        let (b, _) = key_of(&request(r#"{"b":2,"a":1,"password":"y"}"#)).unwrap();
+        assert_eq!(a, b);
+
+        let (a, _) = key_of(&request(r#"{"items":[{"sku":"A","qty":1}],"to":{"suburb":"Mosman","postcode":"2088"}}"#)).unwrap();
+        let (b, _) = key_of(&request(r#"{"to":{"postcode":"2088","suburb":"Mosman"},"items":[{"qty":1,"sku":"A"}]}"#)).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(canonical(&serde_json::json!({"b": [{"d": 1, "c": 2}], "a": null})), br#"{"a":null,"b":[{"c":2,"d":1}]}"#);
+    }
+}
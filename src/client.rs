@@ -1,17 +1,210 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use base64::Engine;
 use num_traits::{Float,Unsigned};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use restson::{RestClient, blocking::RestClient as BRestClient};
+use serde_json::Value;
+use url::Url;
 
 use crate::Error;
+use crate::retry::RetryPolicy;
+use crate::logging::ApiCall;
+use crate::transport::{Method,Request,Response,Transport,UreqTransport};
+use crate::money::Amount;
 use crate::account::{Account,AuthenticateWith,Member};
-use crate::booking::{BookingRequest,BookingResponse};
+use crate::document::{Document,DocumentKind};
+use crate::courier::Courier;
+use crate::booking::{BookingConfirmation,BookingQuery,BookingRequest,BookingResponse,BookingSort,SortDirection};
+use crate::order::{Order,OrderBulk,OrderGroup,OrderQuery,OrderStatus};
+use crate::tracking::{TrackingEvent,TrackingGroup,TrackingReference};
+use crate::locations::{Location,LocationGroup};
+
+static PRODUCTION_ENDPOINT: &str = "https://www.transdirect.com.au/api/";
+static MOCK_ENDPOINT: &str = "https://private-anon-a28d0f1a72-transdirectapiv4.apiary-mock.com/api/";
+
+/// Enum describing which server a client talks to
+/// 
+/// `Mock` is the Apiary mock server described by the
+/// [specification](https://transdirectapiv4.docs.apiary.io/), which accepts
+/// any credentials and returns canned responses. `Custom` takes the base URL
+/// of any other server (staging proxies, local stand-ins, etc.); a `/` is
+/// added to its end if missing, so relative paths are joined onto it.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Environment {
+    #[default]
+    Production,
+    Mock,
+    Custom(String),
+}
+
+impl Environment {
+    /// The base URL every request path is joined onto
+    pub fn base_url(&self) -> &str {
+        match self {
+            Self::Production => PRODUCTION_ENDPOINT,
+            Self::Mock       => MOCK_ENDPOINT,
+            Self::Custom(url) => url,
+        }
+    }
+}
+
+/// Builder for [`Client`] (and, with the `async` feature,
+/// [`crate::async_client::AsyncClient`])
+/// 
+/// Chooses the endpoint, user agent and any headers sent with every request
+/// at runtime. Authentication is still done afterwards through `auth`.
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// use transdirect::client::{ClientBuilder, Environment};
+/// 
+/// let c = ClientBuilder::new()
+///     .environment(Environment::Mock)
+///     .user_agent("warehouse/1.0")
+///     .header("X-Request-Source", "packing-station-3")
+///     .build()
+///     .expect("Valid base URL");
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientBuilder<'a> {
+    environment: Environment,
+    user_agent: Option<String>,
+    headers: Vec<(&'static str, String)>,
+    retry: RetryPolicy,
+    log_bodies: bool,
+    transport: Option<Arc<dyn Transport>>,
+    #[cfg(feature = "async")]
+    async_transport: Option<Arc<dyn crate::transport::AsyncTransport>>,
+    sender: Option<&'a Account>,
+}
+
+impl fmt::Debug for ClientBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("environment", &self.environment)
+            .field("user_agent", &self.user_agent)
+            .field("headers", &self.headers)
+            .field("retry", &self.retry)
+            .field("log_bodies", &self.log_bodies)
+            .field("sender", &self.sender)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> ClientBuilder<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Shorthand for `environment(Environment::Custom(url))`
+    pub fn base_url(self, url: &str) -> Self {
+        self.environment(Environment::Custom(url.to_string()))
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Adds a header sent with every request. Later values for the same
+    /// header, in any case, replace earlier ones, and an `Accept` header
+    /// replaces the default of `application/json`.
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+
+    pub fn sender(mut self, sender: &'a Account) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Sets how failed requests are retried; see [`RetryPolicy`]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Logs request and response bodies at `DEBUG` level, with credentials
+    /// redacted; see [`crate::logging`]. Off by default, as bodies contain
+    /// customers' names and addresses.
+    pub fn log_bodies(mut self, log_bodies: bool) -> Self {
+        self.log_bodies = log_bodies;
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default
+    /// [`UreqTransport`]; see [`crate::transport`]
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sends the requests of an async client through `transport` instead of
+    /// the default [`crate::transport::HyperTransport`]
+    #[cfg(feature = "async")]
+    pub fn async_transport(mut self, transport: impl crate::transport::AsyncTransport + 'static) -> Self {
+        self.async_transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn build(self) -> Result<Client<'a>, Error> {
+        Ok(Client {
+            authenticated: false,
+            transport: self.transport.clone().unwrap_or_else(|| Arc::new(UreqTransport::new())),
+            base_url: self.parse_base_url()?,
+            headers: self.default_headers(),
+            retry: self.retry,
+            log_bodies: self.log_bodies,
+            member: None,
+            sender: self.sender,
+        })
+    }
+
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<crate::async_client::AsyncClient<'a>, Error> {
+        let transport = self.async_transport
+            .clone()
+            .unwrap_or_else(|| Arc::new(crate::transport::HyperTransport::new()));
+
+        Ok(crate::async_client::AsyncClient::from_parts(
+            transport,
+            self.parse_base_url()?,
+            self.default_headers(),
+            self.retry,
+            self.log_bodies,
+            self.sender,
+        ))
+    }
+
+    // Relative paths are joined onto the base URL, so it must end in a `/`
+    fn parse_base_url(&self) -> Result<Url, Error> {
+        let mut url = Url::parse(self.environment.base_url()).map_err(|e| Error::Transport(Box::new(e)))?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
 
-static API_ENDPOINT: &str = if cfg!(test) { 
-    "https://private-anon-a28d0f1a72-transdirectapiv4.apiary-mock.com/api/" }
-    else {
-    "https://www.transdirect.com.au/api/"
-};
+        Ok(url)
+    }
+
+    fn default_headers(&self) -> Vec<(&'static str, String)> {
+        self.user_agent
+            .iter()
+            .map(|ua| ("User-Agent", ua.clone()))
+            .chain(self.headers.iter().cloned())
+            .fold(Vec::new(), merge_header)
+    }
+}
 
 /// Client object for interacting with the API
 /// 
@@ -20,9 +213,16 @@ static API_ENDPOINT: &str = if cfg!(test) {
 /// constructed the constructors [`new`], [`from_auth`], [`from_basic_auth`],
 /// or [`from_apikey`].
 /// 
-/// Creates a synchronous (currently) client. Optimistically, we will implement
-/// an async version through tokio, but I have absolutely no idea what that
-/// entails.
+/// Creates a synchronous client. An asynchronous equivalent with the same
+/// methods is available as [`crate::async_client::AsyncClient`] behind the
+/// `async` feature.
+/// 
+/// Failed requests are retried according to the client's [`RetryPolicy`],
+/// which by default retries reads up to three times. Each call is
+/// traced as a `transdirect_request` span; see [`crate::logging`].
+/// 
+/// Requests are sent through a [`Transport`], which is [`UreqTransport`]
+/// unless another is given to [`ClientBuilder::transport`].
 /// 
 /// # Examples
 /// This example details the basic task of retrieving a quote from the
@@ -34,18 +234,24 @@ static API_ENDPOINT: &str = if cfg!(test) {
 /// ```
 pub struct Client<'a> {
     authenticated: bool,
-    restclient: BRestClient, // restson seems to have no advantages over reqwest
+    transport: Arc<dyn Transport>,
+    base_url: Url,
+    headers: Vec<(&'static str, String)>, // Including credentials
+    retry: RetryPolicy,
+    log_bodies: bool,
+    member: Option<Member>, // As of authentication or the last update
     pub sender: Option<&'a Account>, // Should eventually be default
 }
 
 impl<'a> Client<'a> {
     pub fn new() -> Self {
-        Self {
-            authenticated: false,
-            restclient: RestClient::new_blocking(API_ENDPOINT)
-                .expect("Should be a valid URL or connected to the internet"),
-            sender: None
-        }
+        ClientBuilder::new()
+            .build()
+            .expect("Should be a valid URL or connected to the internet")
+    }
+
+    pub fn builder() -> ClientBuilder<'a> {
+        ClientBuilder::new()
     }
     
     pub fn from_auth(auth: AuthenticateWith) -> Result<Self, Error> {
@@ -67,27 +273,54 @@ impl<'a> Client<'a> {
     pub fn auth(&mut self, auth: AuthenticateWith) -> Result<(), Error> {
         use AuthenticateWith::*;
 
+        self.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Authorization") && !name.eq_ignore_ascii_case("Api-key"));
+
         match auth {
-            Basic(user, pass) => self.restclient.set_auth(user, pass),
-            APIKey(key) => self.restclient.set_header("Api-key", key).expect("Should be able to set Api-key header"),
-        }
-        
-        match self.restclient.get::<_, Member>(()) {
-            Ok(_) => {
-                self.authenticated = true;
-                Ok(())
+            Basic(user, pass) => {
+                let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+                self.headers.push(("Authorization", format!("Basic {credentials}")));
             },
-            Err(err) => Err(Error::HTTPError(err.to_string())),
+            APIKey(key) => self.headers.push(("Api-key", key.to_string())),
         }
+        
+        self.refresh_member::<f64>()?;
+        self.authenticated = true;
+
+        Ok(())
+    }
+
+    /// The profile of the authenticated member, as of authentication or the
+    /// last call to [`Client::refresh_member`] or [`Client::update_member`]
+    pub fn member(&self) -> Option<&Member> {
+        self.member.as_ref()
+    }
+
+    /// Fetches the member's profile again, e.g. for an up-to-date balance
+    pub fn refresh_member<M>(&mut self) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned {
+        let member = self.execute::<Value>(ApiRequest::get(path::<Member, _>(())?))?;
+        self.keep_member(member)
+    }
+
+    /// Updates the member's profile, returning it as updated by the server
+    pub fn update_member<M>(&mut self, member: &Member<M>) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned + Serialize {
+        let member = self.execute::<Value>(ApiRequest::put(path::<Member<M>, _>(())?).body(member)?)?;
+        self.keep_member(member)
+    }
+
+    // Keeps the profile returned by the server, and reads it as `Member<M>`
+    fn keep_member<M>(&mut self, member: Value) -> Result<Member<M>, Error>
+    where M: Amount + DeserializeOwned {
+        self.member = Some(serde_json::from_value(member.clone()).map_err(|e| Error::Transport(Box::new(e)))?);
+        serde_json::from_value(member).map_err(|e| Error::Transport(Box::new(e)))
     }
     
-    pub fn quotes<'b, T, U>(&self, request: &'b BookingRequest<T, U>) -> Result<BookingResponse<T, U>, Error>
-    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize {
-        self
-            .restclient
-            .post_capture::<_, _, BookingResponse<T, U>>((), request)
-            .map(|s| s.into_inner())
-            .map_err(|e| Error::HTTPError(e.to_string())) // Eventually remove entirely
+    pub fn quotes<T, U, M>(&self, request: &BookingRequest<T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::post(path::<BookingRequest<T, U, M>, _>(())?)
+            .body(request)?
+            .idempotent(self.retry.retry_quotes))
     }
     
     /// Gets a copy of a booking from its id; note that this is
@@ -103,52 +336,589 @@ impl<'a> Client<'a> {
     /// let oldbooking: BookingResponse = c.booking(623630).expect("Valid booking");
     /// // Do something interesting
     /// # // oldbooking.update()
-    pub fn booking<T, U>(&self, booking_id: u32) -> Result<BookingResponse<T, U>, Error>
-    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned {
-        self
-            .restclient
-            .get::<_, BookingResponse<T, U>>(booking_id)
-            .map(|s| s.into_inner())
-            .map_err(|e| Error::HTTPError(e.to_string()))
+    pub fn booking<T, U, M>(&self, booking_id: u32) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<BookingResponse<T, U, M>, _>(booking_id)?).booking(booking_id))
+    }
+    
+    /// Replaces the details of an existing booking, returning it as updated
+    /// by the server. Only bookings that have not been confirmed can be
+    /// updated.
+    pub fn update_booking<T, U, M>(&self, booking_id: u32, request: &BookingRequest<T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::put(path::<BookingRequest<T, U, M>, _>(booking_id)?)
+            .body(request)?
+            .booking(booking_id))
+    }
+
+    /// Cancels a booking, returning it with its new status
+    pub fn cancel_booking<T, U, M>(&self, booking_id: u32) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute::<()>(ApiRequest::delete(path::<BookingResponse<T, U, M>, _>(booking_id)?).booking(booking_id))?;
+
+        self.booking(booking_id)
+    }
+
+    /// Confirms a booking with one of its quoted couriers, to be picked up
+    /// on `pickup_date` (which should be one of that quote's `pickup_dates`)
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::{BookingResponse, Courier};
+    /// use transdirect::TransdirectClient as Client;
+    /// let c = Client::new();
+    /// //...
+    /// let date = time::Date::from_calendar_date(2023, time::Month::March, 14).unwrap();
+    /// let booking: BookingResponse = c.confirm_booking(623630, &Courier::Toll, date).expect("Confirmed");
+    /// ```
+    pub fn confirm_booking<T, U, M>(&self, booking_id: u32, courier: &Courier, pickup_date: time::Date) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        let confirmation = BookingConfirmation {
+            courier: courier.clone(),
+            pickup_date,
+        };
+
+        self.execute::<()>(ApiRequest::post(path::<BookingConfirmation, _>(booking_id)?)
+            .body(&confirmation)?
+            .booking(booking_id)
+            .discard_response())?;
+
+        self.booking(booking_id)
+    }
+    
+    /// Lists the bookings matching `query`, fetching a page at a time as the
+    /// iterator is advanced
+    /// 
+    /// Iteration stops after the last page, or after yielding the first
+    /// error. The last page is the one the server's pagination says is the
+    /// last (or, without pagination metadata, a short one), or one with no
+    /// bookings which were not already yielded, and no booking is yielded
+    /// twice.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::{BookingQuery, BookingResponse, BookingStatus};
+    /// use transdirect::TransdirectClient as Client;
+    /// let c = Client::new();
+    /// //...
+    /// let query = BookingQuery::new().status(BookingStatus::Confirmed).per_page(100);
+    /// for booking in c.bookings(&query) {
+    ///     let booking: BookingResponse = booking.expect("Listed");
+    ///     println!("{}: {:?}", booking.id, booking.connote);
+    /// }
+    /// ```
+    pub fn bookings<'c, T, U, M>(&'c self, query: &BookingQuery) -> Bookings<'c, 'a, T, U, M>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        Bookings {
+            client: self,
+            pager: Pager::new(query),
+            page: Vec::new().into_iter(),
+        }
+    }
+
+    /// Fetches the single page of bookings selected by `query`
+    pub fn bookings_page<T, U, M>(&self, query: &BookingQuery) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.bookings_group(query).map(BookingResponseGroup::into_bookings)
+    }
+
+    fn bookings_group<T, U, M>(&self, query: &BookingQuery) -> Result<BookingResponseGroup<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<BookingResponseGroup<T, U, M>, _>(())?).query(query.to_pairs()))
     }
     
-    pub fn bookings_after_date<T, U>(&self, date: time::OffsetDateTime)
-    -> Result<Vec<BookingResponse<T, U>>, Error>
-    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned {
+    pub fn bookings_after_date<T, U, M>(&self, date: time::OffsetDateTime)
+    -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
         self.bookings_after_date_sort_by(date, "")
     }
 
-    pub fn bookings_sort_by<T, U>(&self, field: &str) -> Result<Vec<BookingResponse<T, U>>, Error>
-    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned {
+    pub fn bookings_sort_by<T, U, M>(&self, field: &str) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
         self.bookings_after_date_sort_by(time::OffsetDateTime::UNIX_EPOCH, field)
     }    
 
-    pub fn bookings_after_date_sort_by<T, U>(&self, date: time::OffsetDateTime, field: &str) -> Result<Vec<BookingResponse<T, U>>, Error>
-    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned {
-        self
-            .restclient
-            .get::<_, BookingResponseGroup<T, U>>((date, field))
-            .map(|s| s.into_inner().0)
-            .map_err(|e| Error::HTTPError(e.to_string()))
+    /// Every booking since `date`, sorted by `field` (e.g. `"booking_time"`)
+    /// unless it is empty. Prefer [`Client::bookings`].
+    pub fn bookings_after_date_sort_by<T, U, M>(&self, date: time::OffsetDateTime, field: &str) -> Result<Vec<BookingResponse<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.bookings(&legacy_query(date, field)).collect()
+    }
+
+    /// Creates an order to be booked at a later date
+    pub fn create_order<T, U, M>(&self, order: &Order<T, U, M>) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::post(path::<Order<T, U, M>, _>(())?).body(order)?)
+    }
+
+    /// Creates many orders in a single request
+    pub fn create_orders<T, U, M>(&self, orders: &[Order<T, U, M>]) -> Result<Vec<Order<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute::<OrderGroup<T, U, M>>(ApiRequest::post(path::<OrderBulk<T, U, M>, _>(())?).body(&OrderBulk(orders))?)
+            .map(|group| group.0)
+    }
+
+    /// Lists the orders matching every filter set in `query`
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::{Order, OrderQuery, OrderStatus};
+    /// use transdirect::TransdirectClient as Client;
+    /// let c = Client::new();
+    /// //...
+    /// let query = OrderQuery { status: Some(OrderStatus::Pending), ..OrderQuery::default() };
+    /// let pending: Vec<Order> = c.orders(&query).expect("Valid orders");
+    /// ```
+    pub fn orders<T, U, M>(&self, query: &OrderQuery) -> Result<Vec<Order<T, U, M>>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute::<OrderGroup<T, U, M>>(ApiRequest::get(path::<OrderGroup<T, U, M>, _>(())?).query(query.to_pairs()))
+            .map(|group| group.0)
+    }
+
+    /// Gets a copy of an order from its (Transdirect) id
+    pub fn order<T, U, M>(&self, order_id: u32) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+        self.execute(ApiRequest::get(path::<Order<T, U, M>, _>(order_id)?))
+    }
+
+    /// Replaces the details of an existing order, returning it as updated
+    /// by the server
+    pub fn update_order<T, U, M>(&self, order_id: u32, order: &Order<T, U, M>) -> Result<Order<T, U, M>, Error>
+    where T: Unsigned + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.execute(ApiRequest::put(path::<Order<T, U, M>, _>(order_id)?).body(order)?)
+    }
+
+    pub fn delete_order(&self, order_id: u32) -> Result<(), Error> {
+        self.execute(ApiRequest::delete(path::<Order<u32, f64>, _>(order_id)?)) // Parameters only pick the path
+    }
+
+    /// Gets quotes for delivering an order; see [`BookingRequest::from`]
+    pub fn quote_order<T, U, M>(&self, order: &Order<T, U, M>) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + Clone + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        self.quotes(&BookingRequest::from(order))
+    }
+
+    /// Quotes an order and confirms the booking with `courier`
+    /// 
+    /// Once the booking is confirmed, the order is marked
+    /// [`OrderStatus::Booked`] along with the courier and connote, and saved
+    /// to the server if it has been created there (i.e. has an `id`).
+    pub fn book_order<T, U, M>(&self, order: &mut Order<T, U, M>, courier: &Courier, pickup_date: time::Date) -> Result<BookingResponse<T, U, M>, Error>
+    where T: Unsigned + Clone + DeserializeOwned + Serialize, U: Float + DeserializeOwned + Serialize, M: Amount + DeserializeOwned + Serialize {
+        let quote = self.quote_order(order)?;
+        let booking = self.confirm_booking::<T, U, M>(quote.id, courier, pickup_date)?;
+
+        if booking.is_confirmed() {
+            order.status = OrderStatus::Booked;
+            order.selected_courier = Some(courier.clone());
+            order.connote = booking.connote.clone();
+
+            if let Some(id) = order.id {
+                *order = self.update_order(id, order)?;
+            }
+        }
+
+        Ok(booking)
+    }
+
+    /// Downloads the shipping label for a booking
+    /// 
+    /// The label is only available once the booking has been confirmed.
+    pub fn label(&self, booking_id: u32) -> Result<Document, Error> {
+        let booking = self.booking::<u32, f64, f64>(booking_id)?; // Parameters are irrelevant
+
+        if booking.label.is_empty() {
+            self.document(booking_id, DocumentKind::Label)
+        } else {
+            self.download(DocumentKind::Label, &booking.label)
+        }
+    }
+
+    /// Downloads one of the documents attached to a booking
+    pub fn document(&self, booking_id: u32, kind: DocumentKind) -> Result<Document, Error> {
+        self.download(kind, &kind.path(booking_id))
+    }
+
+    // Fetches a document from a link, which may be absolute or relative to
+    // the API. Credentials are only sent to the API itself.
+    fn download(&self, kind: DocumentKind, link: &str) -> Result<Document, Error> {
+        let url = self.base_url.join(link.trim_start_matches('/')).map_err(|e| Error::Transport(Box::new(e)))?;
+        let trusted = is_within(&self.base_url, &url);
+        // Links may be presigned, with a token in the query string
+        let call = ApiCall::new("GET", url.path(), self.log_bodies);
+
+        let request = Request {
+            method: Method::Get,
+            url: url.into(),
+            headers: self.headers
+                .iter()
+                .filter(|(name, _)| trusted || !(name.eq_ignore_ascii_case("Authorization") || name.eq_ignore_ascii_case("Api-key")))
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            body: None,
+        };
+        let response = self.send(call, &request, true)?;
+
+        Ok(Document {
+            kind,
+            content_type: response.content_type().map(str::to_string),
+            bytes: response.body,
+        })
+    }
+
+    /// Lists the tracking events for a shipment, oldest first
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::TransdirectClient as Client;
+    /// let c = Client::new();
+    /// //...
+    /// let events = c.tracking("TD00001234").expect("Valid connote");
+    /// if let Some(latest) = events.last() {
+    ///     println!("{}: {}", latest.timestamp, latest.status());
+    /// }
+    /// ```
+    pub fn tracking<'r>(&self, reference: impl Into<TrackingReference<'r>>) -> Result<Vec<TrackingEvent>, Error> {
+        let reference = reference.into();
+        let booking_id = match reference {
+            TrackingReference::Booking(id) => Some(id),
+            _ => None,
+        };
+        let request = ApiRequest::get(path::<TrackingGroup, _>(reference)?);
+
+        self.execute::<TrackingGroup>(ApiRequest { booking_id, ..request })
+            .map(TrackingGroup::into_events)
+    }
+
+    /// Searches for suburbs by postcode or by the start of their name, e.g.
+    /// for address autocomplete
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::{Account, TransdirectClient as Client};
+    /// let c = Client::new();
+    /// //...
+    /// let locations = c.locations("mosm").expect("Searched");
+    /// if let Some(location) = locations.into_iter().next() {
+    ///     let receiver = Account { name: "Jane Doe".to_string(), ..Account::from(location) };
+    /// }
+    /// ```
+    pub fn locations(&self, query: &str) -> Result<Vec<Location>, Error> {
+        self.execute::<LocationGroup>(ApiRequest::get(path::<LocationGroup, _>(())?).query(vec![("q", query.trim().to_string())]))
+            .map(LocationGroup::into_locations)
+    }
+
+    // Sends a request to the API and reads the response as `R`
+    fn execute<R>(&self, request: ApiRequest) -> Result<R, Error>
+    where R: DeserializeOwned {
+        let call = ApiCall::new(request.method.as_str(), &request.path, self.log_bodies).booking(request.booking_id);
+        let response = self.send(call, &request.to_http(&self.base_url, &self.headers)?, self.retry.permits(request.method, request.idempotent))?;
+
+        request.read(&response)
+    }
+
+    // Sends a request through the transport, retrying and tracing it
+    fn send(&self, call: ApiCall, request: &Request, idempotent: bool) -> Result<Response, Error> {
+        let call = call.request(request);
+        let response = call.span().in_scope(|| {
+            self.retry.run(idempotent, || self.transport.send(request)?.error_for_status())
+        });
+        call.finish(response.as_ref());
+
+        response
     }
 }
 
+// Whether a URL is on the same server as the base URL, and under its path
+fn is_within(base_url: &Url, url: &Url) -> bool {
+    url.scheme() == base_url.scheme()
+        && url.host() == base_url.host()
+        && url.port_or_known_default() == base_url.port_or_known_default()
+        && url.path().starts_with(base_url.path()) // Which ends in a `/`
+}
+
+// A page of bookings, either as a bare list or wrapped along with the
+// paginator's metadata, at the top level or under `meta`
 #[derive(Deserialize)]
-struct BookingResponseGroup<T, U>(Vec<BookingResponse<T, U>>)
-where T: Unsigned, U: Float;
+#[serde(untagged)]
+pub(crate) enum BookingResponseGroup<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    Paginated {
+        data: Vec<BookingResponse<T, U, M>>,
+        #[serde(default)]
+        meta: PageMeta,
+        #[serde(flatten)]
+        top: PageMeta,
+    },
+    List(Vec<BookingResponse<T, U, M>>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct PageMeta {
+    current_page: Option<u32>,
+    last_page: Option<u32>,
+}
+
+impl<T, U, M> BookingResponseGroup<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    // Whether the server said this is the last page, if it said at all
+    fn is_last_page(&self) -> Option<bool> {
+        match self {
+            Self::Paginated { meta, top, .. } => [meta, top]
+                .into_iter()
+                .find_map(|m| Some(m.current_page? >= m.last_page?)),
+            Self::List(_) => None,
+        }
+    }
+
+    pub(crate) fn into_bookings(self) -> Vec<BookingResponse<T, U, M>> {
+        match self {
+            Self::Paginated { data, .. } | Self::List(data) => data,
+        }
+    }
+}
+
+// Walks the pages of a listing of bookings for both clients, deciding when
+// it has run out
+pub(crate) struct Pager {
+    query: BookingQuery, // For the next page
+    seen: HashSet<u32>,
+    done: bool,
+}
+
+impl Pager {
+    pub(crate) fn new(query: &BookingQuery) -> Self {
+        Pager { query: query.clone(), seen: HashSet::new(), done: false }
+    }
+
+    /// The query for the next page, unless the listing has run out
+    pub(crate) fn next_query(&self) -> Option<&BookingQuery> {
+        (!self.done).then_some(&self.query)
+    }
+
+    pub(crate) fn fail(&mut self) {
+        self.done = true;
+    }
+
+    /// The bookings of a page which were not on an earlier one
+    ///
+    /// The page the server says is the last ends the listing. Without
+    /// pagination metadata, a short page does instead, as the server may
+    /// otherwise be capping the page size. Either way, so does a page with
+    /// nothing new on it (e.g. a server ignoring the page number and
+    /// repeating itself), which would otherwise go on forever.
+    pub(crate) fn turn<T, U, M>(&mut self, group: BookingResponseGroup<T, U, M>) -> Vec<BookingResponse<T, U, M>>
+    where T: Unsigned, U: Float, M: Amount {
+        let last = group.is_last_page();
+        let page = group.into_bookings();
+        let last = last.unwrap_or(page.len() < self.query.page_size() as usize);
+
+        let fresh: Vec<_> = page.into_iter().filter(|b| self.seen.insert(b.id)).collect();
+        self.done = last || fresh.is_empty();
+        self.query = self.query.clone().next_page();
+        fresh
+    }
+}
+
+impl Default for Client<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U, M> RestPath<()> for BookingResponseGroup<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("bookings/v4".to_string()) }
+}
+
+// The query behind the older `bookings_*` methods, which sent any field
+// they were given
+pub(crate) fn legacy_query(since: time::OffsetDateTime, sort: &str) -> BookingQuery {
+    let query = BookingQuery::new().since(since);
 
-impl<T, U> restson::RestPath<(time::OffsetDateTime, &str)> for BookingResponseGroup<T, U>
-where T: Unsigned, U: Float {
-    fn get_path((since, sort): (time::OffsetDateTime, &str)) -> Result<String, restson::Error> {
-        Ok(format!("bookings/v4/?since={since}&sort={sort}"))
+    if sort.is_empty() {
+        query
+    } else {
+        let field = sort.parse().unwrap_or_else(|_| BookingSort::Other(sort.to_string()));
+        query.sort(field, SortDirection::Ascending)
     }
 }
 
+/// The bookings matching a [`BookingQuery`], fetched a page at a time; see
+/// [`Client::bookings`]
+pub struct Bookings<'c, 'a, T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    client: &'c Client<'a>,
+    pager: Pager,
+    page: std::vec::IntoIter<BookingResponse<T, U, M>>,
+}
+
+impl<T, U, M> Iterator for Bookings<'_, '_, T, U, M>
+where T: Unsigned + DeserializeOwned, U: Float + DeserializeOwned, M: Amount + DeserializeOwned {
+    type Item = Result<BookingResponse<T, U, M>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(booking) = self.page.next() {
+                return Some(Ok(booking));
+            }
+            let query = self.pager.next_query()?;
+
+            match self.client.bookings_group(query) {
+                Ok(group) => self.page = self.pager.turn(group).into_iter(),
+                Err(err) => {
+                    self.pager.fail();
+                    return Some(Err(err));
+                },
+            }
+        }
+    }
+}
+
+// Adds a header, replacing any earlier one of the same name in any case, so
+// transports never see a header twice
+fn merge_header(mut headers: Vec<(&'static str, String)>, (name, value): (&'static str, String)) -> Vec<(&'static str, String)> {
+    headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    headers.push((name, value));
+    headers
+}
+
+/// Where a resource lives in the API, relative to the base URL
+///
+/// `P` is whatever identifies one resource of the kind, e.g. `u32` for a
+/// booking id, or `()` for the collection.
+pub trait RestPath<P> {
+    fn get_path(params: P) -> Result<String, Error>;
+}
+
+/// The path of `R` in the API, as given by its `RestPath`
+pub(crate) fn path<R, P>(params: P) -> Result<String, Error>
+where R: RestPath<P> {
+    R::get_path(params)
+}
+
+// A call to the API, shared by the blocking and async clients, before it is
+// turned into a transport `Request`
+#[derive(Debug, Clone)]
+pub(crate) struct ApiRequest {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) query: Vec<(&'static str, String)>,
+    pub(crate) body: Option<Value>,
+    pub(crate) booking_id: Option<u32>, // Only used for tracing
+    pub(crate) idempotent: bool,
+    pub(crate) discard_response: bool,
+}
+
+impl ApiRequest {
+    fn new(method: Method, path: String) -> Self {
+        Self {
+            method,
+            path,
+            query: Vec::new(),
+            body: None,
+            booking_id: None,
+            idempotent: method != Method::Post,
+            discard_response: method == Method::Delete,
+        }
+    }
+
+    pub(crate) fn get(path: String) -> Self {
+        Self::new(Method::Get, path)
+    }
+
+    pub(crate) fn post(path: String) -> Self {
+        Self::new(Method::Post, path)
+    }
+
+    pub(crate) fn put(path: String) -> Self {
+        Self::new(Method::Put, path)
+    }
+
+    pub(crate) fn delete(path: String) -> Self {
+        Self::new(Method::Delete, path)
+    }
+
+    pub(crate) fn body<B>(mut self, body: &B) -> Result<Self, Error>
+    where B: Serialize + ?Sized {
+        self.body = Some(serde_json::to_value(body).map_err(|e| Error::Transport(Box::new(e)))?);
+        Ok(self)
+    }
+
+    pub(crate) fn query(mut self, query: Vec<(&'static str, String)>) -> Self {
+        self.query = query;
+        self
+    }
+
+    pub(crate) fn booking(mut self, booking_id: u32) -> Self {
+        self.booking_id = Some(booking_id);
+        self
+    }
+
+    pub(crate) fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    /// The server answers with an empty body, which is not JSON
+    pub(crate) fn discard_response(mut self) -> Self {
+        self.discard_response = true;
+        self
+    }
+
+    /// The request as sent to `base_url`, along with `headers`
+    pub(crate) fn to_http(&self, base_url: &Url, headers: &[(&'static str, String)]) -> Result<Request, Error> {
+        let mut url = base_url.join(&self.path).map_err(|e| Error::Transport(Box::new(e)))?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+
+        let body = self.body
+            .as_ref()
+            .map(serde_json::to_vec)
+            .transpose()
+            .map_err(|e| Error::Transport(Box::new(e)))?;
+        let headers = [("Accept", "application/json".to_string())]
+            .into_iter()
+            .chain(headers.iter().cloned())
+            .chain(body.as_ref().map(|_| ("Content-Type", "application/json".to_string())))
+            .fold(Vec::new(), merge_header)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        Ok(Request { method: self.method, url: url.into(), headers, body })
+    }
+
+    /// Reads the response to this request as `R`
+    pub(crate) fn read<R>(&self, response: &Response) -> Result<R, Error>
+    where R: DeserializeOwned {
+        let read = if self.discard_response {
+            serde_json::from_value(Value::Null)
+        } else {
+            serde_json::from_slice(&response.body)
+        };
+
+        read.map_err(|e| Error::Transport(Box::new(e)))
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::*;
     use crate::TransdirectClient as Client;
+    use crate::client::ClientBuilder;
+    use crate::testing::FakeTransdirect;
+    
+    // Stands in for the Apiary mock, so the tests run offline
+    fn mock_client<'a>() -> Client<'a> {
+        FakeTransdirect::new().client()
+    }
     
     fn src_dest() -> (Account, Account){
         (Account { 
@@ -156,10 +926,10 @@ mod tests {
             name: "John Smith".to_string(),
             email: "jsmith@google.com".to_string(),
             postcode: "6008".to_string(),
-            state: "WA".to_string(),
+            state: AustralianState::WesternAustralia,
             suburb: "East Perth".to_string(),
-            kind: "business".to_string(),
-            country: "AU".to_string(),
+            kind: AccountKind::Business,
+            country: Country::AU,
             company_name: "Royal Australian Mint".to_string()
         },
         Account {
@@ -167,10 +937,10 @@ mod tests {
             name: "Jane Doe".to_string(),
             email: "jdoe@google.com".to_string(),
             postcode: "2008".to_string(),
-            state: "NSW".to_string(),
+            state: AustralianState::NewSouthWales,
             suburb: "Mosman".to_string(),
-            kind: "residential".to_string(),
-            country: "AU".to_string(),
+            kind: AccountKind::Residential,
+            country: Country::AU,
             company_name: "Sydney Harbour Operations Ltd.".to_string()
         }
         )
@@ -178,7 +948,7 @@ mod tests {
     
     #[test]
     fn should_get_response() {
-        let c = Client::new();
+        let c = mock_client();
         let items = vec![Product { weight: 2.0, quantity: 1, dimensions: Dimensions { length: 5.0f64, width: 5.0f64, height: 5.0f64 }, ..Product::new() }];
         let (sender, receiver) = src_dest();
         let b = BookingRequest {
@@ -196,20 +966,135 @@ mod tests {
     
     #[test]
     fn should_get_booking() {
-        let c = Client::new();
-        let booking = c.booking::<u32, f64>(623630);
+        let c = mock_client();
+        let (sender, receiver) = src_dest();
+        let b = BookingRequest { items: vec![Product::new()], sender: Some(&sender), receiver: Some(&receiver), ..BookingRequest::default() };
+        let quote = c.quotes(&b).expect("Should be quoted");
+        let booking = c.booking::<u32, f64, f64>(quote.id);
 
         assert!(booking.is_ok());
     }
     
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<transport::Request>>);
+
+    impl transport::Transport for Recorder {
+        fn send(&self, request: &transport::Request) -> Result<transport::Response, Error> {
+            self.0.lock().unwrap().push(request.clone());
+            Ok(transport::Response { status: 204, ..transport::Response::default() })
+        }
+    }
+
     #[test]
-    fn should_get_all_bookings() {
-        let c = Client::new();
-        let m = c.bookings_after_date_sort_by::<u32, f64>(time::OffsetDateTime::UNIX_EPOCH, "booking_time");
-        
-        match m {
-            Ok(l) => println!("{:?}", l),
-            Err(e) => println!("{:?}", e)
+    fn should_send_through_transport() {
+        use std::sync::Arc;
+
+        let recorder = Arc::new(Recorder::default());
+        let c = ClientBuilder::new()
+            .base_url("http://localhost:8080/api/")
+            .transport(recorder.clone())
+            .build()
+            .expect("Valid base URL");
+
+        c.delete_order(42).expect("Should succeed");
+
+        let requests = recorder.0.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, transport::Method::Delete);
+        assert_eq!(requests[0].url, "http://localhost:8080/api/orders/42");
+        assert_eq!(requests[0].header("accept"), Some("application/json"));
+    }
+
+    #[test]
+    fn should_send_each_header_once() {
+        use std::sync::Arc;
+
+        let recorder = Arc::new(Recorder::default());
+        let c = ClientBuilder::new()
+            .base_url("http://localhost:8080/api/")
+            .user_agent("shop/1.0")
+            .header("X-Request-Source", "web")
+            .header("x-request-source", "mobile")
+            .header("accept", "application/hal+json")
+            .transport(recorder.clone())
+            .build()
+            .expect("Valid base URL");
+
+        c.delete_order(42).expect("Should succeed");
+
+        let requests = recorder.0.lock().unwrap();
+        let headers: Vec<(&str, &str)> = requests[0].headers.iter().map(|(n, v)| (n.as_str(), v.as_str())).collect();
+        assert_eq!(headers, [("User-Agent", "shop/1.0"), ("x-request-source", "mobile"), ("accept", "application/hal+json")]);
+    }
+
+    #[test]
+    fn should_only_send_credentials_to_the_api() {
+        use std::sync::Arc;
+
+        let recorder = Arc::new(Recorder::default());
+        let c = ClientBuilder::new()
+            .base_url("https://api.example.com")
+            .header("Api-key", "secret")
+            .transport(recorder.clone())
+            .build()
+            .expect("Valid base URL");
+
+        for link in ["labels/1.pdf", "https://api.example.com/labels/2.pdf", "https://api.example.com.evil.io/labels/3.pdf",
+            "http://api.example.com/labels/4.pdf", "https://api.example.com:8443/labels/5.pdf"] {
+            c.download(DocumentKind::Label, link).expect("Should download");
         }
+
+        let requests = recorder.0.lock().unwrap();
+        let keys: Vec<_> = requests.iter().map(|r| (r.url.as_str(), r.header("Api-key"))).collect();
+        assert_eq!(keys, [
+            ("https://api.example.com/labels/1.pdf", Some("secret")),
+            ("https://api.example.com/labels/2.pdf", Some("secret")),
+            ("https://api.example.com.evil.io/labels/3.pdf", None),
+            ("http://api.example.com/labels/4.pdf", None),
+            ("https://api.example.com:8443/labels/5.pdf", None),
+        ]);
+    }
+
+    #[test]
+    fn should_get_all_bookings() {
+        let c = mock_client();
+        let (sender, receiver) = src_dest();
+        let b = BookingRequest { items: vec![Product::new()], sender: Some(&sender), receiver: Some(&receiver), ..BookingRequest::default() };
+        let mut ids: Vec<u32> = (0..3).map(|_| c.quotes(&b).expect("Should be quoted").id).collect();
+
+        let bookings = c.bookings_after_date_sort_by::<u32, f64, f64>(time::OffsetDateTime::UNIX_EPOCH, "booking_time")
+            .expect("Should list bookings");
+
+        let mut listed: Vec<u32> = bookings.iter().map(|b| b.id).collect();
+        ids.sort();
+        listed.sort();
+        assert_eq!(listed, ids);
+    }
+
+    #[test]
+    fn should_not_log_document_link_queries() {
+        use std::sync::Arc;
+
+        let c = ClientBuilder::new()
+            .base_url("https://api.example.com/api/")
+            .transport(Arc::new(Recorder::default()))
+            .build()
+            .expect("Valid base URL");
+
+        let logged = crate::logging::tests::captured(|| {
+            c.download(DocumentKind::Label, "https://files.example.com/labels/1.pdf?X-Amz-Signature=secret")
+                .expect("Should download");
+        });
+        assert!(logged.contains("/labels/1.pdf"));
+        assert!(!logged.contains("secret"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_forward_unknown_sort_fields() {
+        let query = client::legacy_query(time::OffsetDateTime::UNIX_EPOCH, "reference").to_pairs();
+        assert!(query.contains(&("sort", "reference".to_string())));
+
+        let query = client::legacy_query(time::OffsetDateTime::UNIX_EPOCH, "booked_at").to_pairs();
+        assert!(query.contains(&("sort", "booking_time".to_string())));
+    }
+}
@@ -0,0 +1,396 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, ser};
+
+use crate::Error;
+
+// Common ways of writing a country other than its code or name
+const ALIASES: [(&str, Country); 16] = [
+    ("aus", Country::AU),
+    ("aust", Country::AU),
+    ("nzl", Country::NZ),
+    ("uk", Country::GB),
+    ("great britain", Country::GB),
+    ("england", Country::GB),
+    ("usa", Country::US),
+    ("united states of america", Country::US),
+    ("america", Country::US),
+    ("vietnam", Country::VN),
+    ("turkey", Country::TR),
+    ("czech republic", Country::CZ),
+    ("ivory coast", Country::CI),
+    ("cape verde", Country::CV),
+    ("swaziland", Country::SZ),
+    ("east timor", Country::TL),
+];
+
+macro_rules! countries {
+    ($($code:ident => $name:literal,)*) => {
+        /// Enum describing the ISO 3166-1 countries, by their alpha-2 codes
+        ///
+        /// Codes are sent and received as is (e.g. `"AU"`). Values from the
+        /// API which are not ISO codes are kept verbatim in `Other`, so they
+        /// never cause a response to be rejected. User input can be parsed
+        /// leniently with [`str::parse`], which also accepts names and a few
+        /// common abbreviations.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use transdirect::Country;
+        ///
+        /// assert_eq!("Australia".parse::<Country>().unwrap(), Country::AU);
+        /// assert_eq!(" nz ".parse::<Country>().unwrap(), Country::NZ);
+        /// assert_eq!("UK".parse::<Country>().unwrap(), Country::GB);
+        /// assert!("Atlantis".parse::<Country>().is_err());
+        /// ```
+        #[non_exhaustive]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Country {
+            $(
+                #[doc = $name]
+                $code,
+            )*
+            Other(String),
+        }
+
+        impl Country {
+            /// Every ISO country, in order of code
+            pub const ALL: &'static [Country] = &[$(Self::$code,)*];
+
+            /// The two-letter code used for this country by the API
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$code => stringify!($code),)*
+                    Self::Other(code) => code,
+                }
+            }
+
+            /// The short English name of the country, unless it is `Other`
+            pub fn name(&self) -> Option<&'static str> {
+                match self {
+                    $(Self::$code => Some($name),)*
+                    Self::Other(_) => None,
+                }
+            }
+
+            // Only exact, upper case codes
+            fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $(stringify!($code) => Some(Self::$code),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+countries! {
+    AD => "Andorra",
+    AE => "United Arab Emirates",
+    AF => "Afghanistan",
+    AG => "Antigua and Barbuda",
+    AI => "Anguilla",
+    AL => "Albania",
+    AM => "Armenia",
+    AO => "Angola",
+    AQ => "Antarctica",
+    AR => "Argentina",
+    AS => "American Samoa",
+    AT => "Austria",
+    AU => "Australia",
+    AW => "Aruba",
+    AX => "Åland Islands",
+    AZ => "Azerbaijan",
+    BA => "Bosnia and Herzegovina",
+    BB => "Barbados",
+    BD => "Bangladesh",
+    BE => "Belgium",
+    BF => "Burkina Faso",
+    BG => "Bulgaria",
+    BH => "Bahrain",
+    BI => "Burundi",
+    BJ => "Benin",
+    BL => "Saint Barthélemy",
+    BM => "Bermuda",
+    BN => "Brunei Darussalam",
+    BO => "Bolivia",
+    BQ => "Bonaire, Sint Eustatius and Saba",
+    BR => "Brazil",
+    BS => "Bahamas",
+    BT => "Bhutan",
+    BV => "Bouvet Island",
+    BW => "Botswana",
+    BY => "Belarus",
+    BZ => "Belize",
+    CA => "Canada",
+    CC => "Cocos (Keeling) Islands",
+    CD => "Democratic Republic of the Congo",
+    CF => "Central African Republic",
+    CG => "Congo",
+    CH => "Switzerland",
+    CI => "Côte d'Ivoire",
+    CK => "Cook Islands",
+    CL => "Chile",
+    CM => "Cameroon",
+    CN => "China",
+    CO => "Colombia",
+    CR => "Costa Rica",
+    CU => "Cuba",
+    CV => "Cabo Verde",
+    CW => "Curaçao",
+    CX => "Christmas Island",
+    CY => "Cyprus",
+    CZ => "Czechia",
+    DE => "Germany",
+    DJ => "Djibouti",
+    DK => "Denmark",
+    DM => "Dominica",
+    DO => "Dominican Republic",
+    DZ => "Algeria",
+    EC => "Ecuador",
+    EE => "Estonia",
+    EG => "Egypt",
+    EH => "Western Sahara",
+    ER => "Eritrea",
+    ES => "Spain",
+    ET => "Ethiopia",
+    FI => "Finland",
+    FJ => "Fiji",
+    FK => "Falkland Islands",
+    FM => "Micronesia",
+    FO => "Faroe Islands",
+    FR => "France",
+    GA => "Gabon",
+    GB => "United Kingdom",
+    GD => "Grenada",
+    GE => "Georgia",
+    GF => "French Guiana",
+    GG => "Guernsey",
+    GH => "Ghana",
+    GI => "Gibraltar",
+    GL => "Greenland",
+    GM => "Gambia",
+    GN => "Guinea",
+    GP => "Guadeloupe",
+    GQ => "Equatorial Guinea",
+    GR => "Greece",
+    GS => "South Georgia and the South Sandwich Islands",
+    GT => "Guatemala",
+    GU => "Guam",
+    GW => "Guinea-Bissau",
+    GY => "Guyana",
+    HK => "Hong Kong",
+    HM => "Heard Island and McDonald Islands",
+    HN => "Honduras",
+    HR => "Croatia",
+    HT => "Haiti",
+    HU => "Hungary",
+    ID => "Indonesia",
+    IE => "Ireland",
+    IL => "Israel",
+    IM => "Isle of Man",
+    IN => "India",
+    IO => "British Indian Ocean Territory",
+    IQ => "Iraq",
+    IR => "Iran",
+    IS => "Iceland",
+    IT => "Italy",
+    JE => "Jersey",
+    JM => "Jamaica",
+    JO => "Jordan",
+    JP => "Japan",
+    KE => "Kenya",
+    KG => "Kyrgyzstan",
+    KH => "Cambodia",
+    KI => "Kiribati",
+    KM => "Comoros",
+    KN => "Saint Kitts and Nevis",
+    KP => "North Korea",
+    KR => "South Korea",
+    KW => "Kuwait",
+    KY => "Cayman Islands",
+    KZ => "Kazakhstan",
+    LA => "Laos",
+    LB => "Lebanon",
+    LC => "Saint Lucia",
+    LI => "Liechtenstein",
+    LK => "Sri Lanka",
+    LR => "Liberia",
+    LS => "Lesotho",
+    LT => "Lithuania",
+    LU => "Luxembourg",
+    LV => "Latvia",
+    LY => "Libya",
+    MA => "Morocco",
+    MC => "Monaco",
+    MD => "Moldova",
+    ME => "Montenegro",
+    MF => "Saint Martin",
+    MG => "Madagascar",
+    MH => "Marshall Islands",
+    MK => "North Macedonia",
+    ML => "Mali",
+    MM => "Myanmar",
+    MN => "Mongolia",
+    MO => "Macao",
+    MP => "Northern Mariana Islands",
+    MQ => "Martinique",
+    MR => "Mauritania",
+    MS => "Montserrat",
+    MT => "Malta",
+    MU => "Mauritius",
+    MV => "Maldives",
+    MW => "Malawi",
+    MX => "Mexico",
+    MY => "Malaysia",
+    MZ => "Mozambique",
+    NA => "Namibia",
+    NC => "New Caledonia",
+    NE => "Niger",
+    NF => "Norfolk Island",
+    NG => "Nigeria",
+    NI => "Nicaragua",
+    NL => "Netherlands",
+    NO => "Norway",
+    NP => "Nepal",
+    NR => "Nauru",
+    NU => "Niue",
+    NZ => "New Zealand",
+    OM => "Oman",
+    PA => "Panama",
+    PE => "Peru",
+    PF => "French Polynesia",
+    PG => "Papua New Guinea",
+    PH => "Philippines",
+    PK => "Pakistan",
+    PL => "Poland",
+    PM => "Saint Pierre and Miquelon",
+    PN => "Pitcairn",
+    PR => "Puerto Rico",
+    PS => "Palestine",
+    PT => "Portugal",
+    PW => "Palau",
+    PY => "Paraguay",
+    QA => "Qatar",
+    RE => "Réunion",
+    RO => "Romania",
+    RS => "Serbia",
+    RU => "Russia",
+    RW => "Rwanda",
+    SA => "Saudi Arabia",
+    SB => "Solomon Islands",
+    SC => "Seychelles",
+    SD => "Sudan",
+    SE => "Sweden",
+    SG => "Singapore",
+    SH => "Saint Helena, Ascension and Tristan da Cunha",
+    SI => "Slovenia",
+    SJ => "Svalbard and Jan Mayen",
+    SK => "Slovakia",
+    SL => "Sierra Leone",
+    SM => "San Marino",
+    SN => "Senegal",
+    SO => "Somalia",
+    SR => "Suriname",
+    SS => "South Sudan",
+    ST => "Sao Tome and Principe",
+    SV => "El Salvador",
+    SX => "Sint Maarten",
+    SY => "Syria",
+    SZ => "Eswatini",
+    TC => "Turks and Caicos Islands",
+    TD => "Chad",
+    TF => "French Southern Territories",
+    TG => "Togo",
+    TH => "Thailand",
+    TJ => "Tajikistan",
+    TK => "Tokelau",
+    TL => "Timor-Leste",
+    TM => "Turkmenistan",
+    TN => "Tunisia",
+    TO => "Tonga",
+    TR => "Türkiye",
+    TT => "Trinidad and Tobago",
+    TV => "Tuvalu",
+    TW => "Taiwan",
+    TZ => "Tanzania",
+    UA => "Ukraine",
+    UG => "Uganda",
+    UM => "United States Minor Outlying Islands",
+    US => "United States",
+    UY => "Uruguay",
+    UZ => "Uzbekistan",
+    VA => "Holy See",
+    VC => "Saint Vincent and the Grenadines",
+    VE => "Venezuela",
+    VG => "British Virgin Islands",
+    VI => "United States Virgin Islands",
+    VN => "Viet Nam",
+    VU => "Vanuatu",
+    WF => "Wallis and Futuna",
+    WS => "Samoa",
+    YE => "Yemen",
+    YT => "Mayotte",
+    ZA => "South Africa",
+    ZM => "Zambia",
+    ZW => "Zimbabwe",
+}
+
+/// Australia, as Transdirect is an Australian service
+impl Default for Country {
+    fn default() -> Self {
+        Self::AU
+    }
+}
+
+impl Country {
+    /// Whether this is an ISO country, rather than `Other`
+    pub fn is_iso(&self) -> bool {
+        !matches!(self, Self::Other(_))
+    }
+}
+
+/// Accepts codes and names in any case, ignoring surrounding whitespace
+impl FromStr for Country {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(country) = Self::from_code(&s.to_ascii_uppercase()) {
+            return Ok(country);
+        }
+
+        let s = s.to_lowercase().replace('.', "");
+        Self::ALL
+            .iter()
+            .find(|c| c.name().is_some_and(|name| name.to_lowercase() == s))
+            .cloned()
+            .or_else(|| ALIASES.iter().find(|(alias, _)| *alias == s).map(|(_, c)| c.clone()))
+            .ok_or(Error::Unrecognised(s))
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for Country {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Country {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let country = String::deserialize(deserializer)?;
+        Ok(country.parse().unwrap_or(Self::Other(country)))
+    }
+}
@@ -0,0 +1,115 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, ser};
+
+/// Enum describing the courier services quoted by Transdirect
+/// 
+/// These are the keys of `BookingResponse.quotes` and the value sent when
+/// confirming a booking. Services not (yet) listed here are kept verbatim in
+/// `Other`, so new couriers never cause a response to be rejected.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Courier {
+    Allied,
+    Aramex,
+    CouriersPleaseDomesticPriority,
+    DirectCouriersRegular,
+    DirectCouriersExpress,
+    DirectCouriersElite,
+    Fastway,
+    HunterExpress,
+    Mainfreight,
+    Northline,
+    StarTrack,
+    StarTrackExpress,
+    Tnt,
+    Toll,
+    TollPriorityOvernight,
+    TollPrioritySameday,
+    Other(String),
+}
+
+impl Courier {
+    /// The key used for this courier by the API
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Allied                         => "allied",
+            Self::Aramex                         => "aramex",
+            Self::CouriersPleaseDomesticPriority => "couriers_please_domestic_priority",
+            Self::DirectCouriersRegular          => "direct_couriers_regular",
+            Self::DirectCouriersExpress          => "direct_couriers_express",
+            Self::DirectCouriersElite            => "direct_couriers_elite",
+            Self::Fastway                        => "fastway",
+            Self::HunterExpress                  => "hunter_express",
+            Self::Mainfreight                    => "mainfreight",
+            Self::Northline                      => "northline",
+            Self::StarTrack                      => "startrack",
+            Self::StarTrackExpress               => "startrack_express",
+            Self::Tnt                            => "tnt",
+            Self::Toll                           => "toll",
+            Self::TollPriorityOvernight          => "toll_priority_overnight",
+            Self::TollPrioritySameday            => "toll_priority_sameday",
+            Self::Other(key)                     => key,
+        }
+    }
+}
+
+impl FromStr for Courier {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "allied"                            => Self::Allied,
+            "aramex"                            => Self::Aramex,
+            "couriers_please_domestic_priority" => Self::CouriersPleaseDomesticPriority,
+            "direct_couriers_regular"           => Self::DirectCouriersRegular,
+            "direct_couriers_express"           => Self::DirectCouriersExpress,
+            "direct_couriers_elite"             => Self::DirectCouriersElite,
+            "fastway"                           => Self::Fastway,
+            "hunter_express"                    => Self::HunterExpress,
+            "mainfreight"                       => Self::Mainfreight,
+            "northline"                         => Self::Northline,
+            "startrack"                         => Self::StarTrack,
+            "startrack_express"                 => Self::StarTrackExpress,
+            "tnt"                               => Self::Tnt,
+            "toll"                              => Self::Toll,
+            "toll_priority_overnight"           => Self::TollPriorityOvernight,
+            "toll_priority_sameday"             => Self::TollPrioritySameday,
+            other                               => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Courier {
+    fn from(s: &str) -> Self {
+        match s.parse() {
+            Ok(courier) => courier,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl fmt::Display for Courier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for Courier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Courier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let variant = String::deserialize(deserializer)?;
+        Ok(Courier::from(variant.as_str()))
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use crate::Error;
+
+/// Kinds of printable documents attached to a booking
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentKind {
+    Label,
+    ConsignmentNote,
+    Manifest,
+}
+
+impl DocumentKind {
+    /// Path of the document relative to the API base URL
+    /// 
+    /// Labels are linked from `BookingResponse.label` instead, but this path
+    /// is used when a booking has no label link yet.
+    pub fn path(&self, booking_id: u32) -> String {
+        match self {
+            Self::Label           => format!("bookings/v4/{booking_id}/label"),
+            Self::ConsignmentNote => format!("bookings/v4/{booking_id}/consignment-note"),
+            Self::Manifest        => format!("bookings/v4/{booking_id}/manifest"),
+        }
+    }
+}
+
+/// A downloaded document (usually a PDF), ready to print or save
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub kind: DocumentKind,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+impl Document {
+    /// Whether the content is a PDF, judging by its header or signature
+    pub fn is_pdf(&self) -> bool {
+        self.content_type.as_deref().is_some_and(|t| t.starts_with("application/pdf"))
+            || self.bytes.starts_with(b"%PDF")
+    }
+
+    /// Writes the document to `path`, replacing any existing file
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use transdirect::TransdirectClient as Client;
+    /// let c = Client::new();
+    /// //...
+    /// c.label(623630)
+    ///     .and_then(|label| label.save("/tmp/623630.pdf"))
+    ///     .expect("Label should be saved");
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, &self.bytes).map_err(Error::Io)
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
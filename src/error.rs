@@ -1,17 +1,250 @@
-use restson::Error as RestsonError;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde_derive::Deserialize;
+use serde::de;
+
+use crate::transport::Response;
+
 /// Errors which can be returned from the Transdirect API
 /// 
-/// 
+/// Failures where the server answered are reported as [`Error::HTTPError`],
+/// which keeps the status code and, where the body could be read, the
+/// [`ApiError`] payload. Anything that stopped a response arriving at all
+/// (DNS, TLS, timeouts, malformed JSON) is an [`Error::Transport`], whose
+/// underlying HTTP client error is available through
+/// [`std::error::Error::source`].
 #[non_exhaustive]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug)]
 pub enum Error {
     UnreadableResponse,
     UnknownStatus,
-    HTTPError(String),
+    Unrecognised(String), // Input which could not be parsed
+    HTTPError {
+        status: u16,
+        api_error: Option<ApiError>,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+    Transport(Box<dyn std::error::Error + Send + Sync + 'static>),
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// The HTTP status code, if the server responded at all
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::HTTPError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The parsed error payload, if the server sent one
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::HTTPError { api_error, .. } => api_error.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the credentials were missing or rejected (401 or 403)
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self.status(), Some(401 | 403))
+    }
+
+    /// Whether the request was rejected as invalid (400 or 422)
+    pub fn is_validation(&self) -> bool {
+        matches!(self.status(), Some(400 | 422))
+    }
+
+    /// Whether the server reported a fault on its end (5xx)
+    pub fn is_server_error(&self) -> bool {
+        matches!(self.status(), Some(500..=599))
+    }
+
+    /// Whether the same request may succeed if sent again: the server was
+    /// rate limiting or failing, or no response arrived at all
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::HTTPError { status, .. } => *status == 429 || (500..=599).contains(status),
+            Self::Transport(err) => !is_malformed(err.as_ref()),
+            _ => false,
+        }
+    }
+
+    /// How long the server asked to wait before retrying, if it said
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::HTTPError { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+// Failures of the request itself, which would fail the same way again
+fn is_malformed(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    #[cfg(feature = "async")]
+    if err.is::<hyper::http::Error>() {
+        return true;
+    }
+    err.is::<serde_json::Error>() || err.is::<url::ParseError>()
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreadableResponse => write!(f, "response from Transdirect could not be read"),
+            Self::UnknownStatus => write!(f, "unrecognised status value"),
+            Self::Unrecognised(value) => write!(f, "unrecognised value {value:?}"),
+            Self::HTTPError { status, api_error: Some(api_error), .. } =>
+                write!(f, "Transdirect returned HTTP {status}: {api_error}"),
+            Self::HTTPError { status, body, .. } if body.is_empty() =>
+                write!(f, "Transdirect returned HTTP {status}"),
+            Self::HTTPError { status, body, .. } =>
+                write!(f, "Transdirect returned HTTP {status}: {body}"),
+            Self::Transport(err) => write!(f, "request to Transdirect failed: {err}"),
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err.as_ref()),
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Error {
+        match err {
+            ureq::Error::Status(status, response) => Response::from_ureq(response)
+                .unwrap_or(Response { status, ..Response::default() }) // Body unreadable
+                .error_for_status()
+                .err()
+                .unwrap_or(Error::UnknownStatus),
+            err => Error::Transport(Box::new(err)),
+        }
+    }
 }
 
-impl From<RestsonError> for Error {
-    fn from(err: RestsonError) -> Error {
-        Error::HTTPError(err.to_string())
+/// Error payload returned by Transdirect alongside a failing status code
+/// 
+/// `errors` maps each offending request field to its validation messages,
+/// and is empty for failures that are not about a particular field.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ApiError {
+    #[serde(default, alias = "error")]
+    pub message: String,
+    #[serde(default, deserialize_with = "deserialize_field_errors")]
+    pub errors: HashMap<String, Vec<String>>,
+}
+
+impl ApiError {
+    /// Parses a response body, returning `None` if it is not an error payload
+    pub fn from_body(body: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(body)
+            .ok()
+            .filter(|e| !e.message.is_empty() || !e.errors.is_empty())
+    }
+
+    /// The validation messages for a single request field
+    pub fn field(&self, field: &str) -> &[String] {
+        self.errors.get(field).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        let mut fields: Vec<_> = self.errors.iter().collect();
+        fields.sort();
+        for (field, messages) in fields {
+            write!(f, "; {field}: {}", messages.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Field errors arrive as either a single message or a list of messages
+fn deserialize_field_errors<'de, D>(deserializer: D) -> Result<HashMap<String, Vec<String>>, D::Error>
+where D: de::Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Messages {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let raw: Option<HashMap<String, Messages>> = de::Deserialize::deserialize(deserializer)?;
+
+    Ok(raw.unwrap_or_default()
+        .into_iter()
+        .map(|(field, messages)| match messages {
+            Messages::One(message) => (field, vec![message]),
+            Messages::Many(messages) => (field, messages),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_error(status: u16, body: &str) -> Error {
+        Response { status, body: body.as_bytes().to_vec(), ..Response::default() }
+            .error_for_status()
+            .expect_err("Should fail")
+    }
+
+    #[test]
+    fn should_parse_validation_errors() {
+        let err = http_error(422, r#"{
+            "message": "The given data was invalid.",
+            "errors": { "sender.postcode": ["Postcode is required"], "items": "At least one item" }
+        }"#);
+
+        assert_eq!(err.status(), Some(422));
+        assert!(err.is_validation());
+        let api_error = err.api_error().expect("Should parse payload");
+        assert_eq!(api_error.field("sender.postcode"), ["Postcode is required"]);
+        assert_eq!(api_error.field("items"), ["At least one item"]);
+    }
+
+    #[test]
+    fn should_keep_unparseable_body() {
+        let err = http_error(502, "Bad Gateway");
+
+        assert!(err.is_server_error());
+        assert!(err.api_error().is_none());
+        assert_eq!(err.to_string(), "Transdirect returned HTTP 502: Bad Gateway");
+    }
+
+    #[test]
+    fn should_expose_transport_source() {
+        let err = Error::Transport(Box::new(std::io::Error::from(std::io::ErrorKind::TimedOut)));
+
+        assert!(std::error::Error::source(&err).is_some());
+        assert!(err.is_retryable());
+        assert_eq!(err.status(), None);
+    }
+
+    #[test]
+    fn should_read_ureq_status_errors() {
+        let response: ureq::Response = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\n\r\n{\"message\":\"Slow down\"}"
+            .parse()
+            .unwrap();
+        let err = Error::from(ureq::Error::Status(429, response));
+
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+        assert_eq!(err.api_error().map(|e| e.message.as_str()), Some("Slow down"));
+        assert!(err.is_retryable());
     }
 }
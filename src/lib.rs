@@ -1,28 +1,85 @@
 pub mod account;
+#[cfg(any(test, feature = "testing"))]
+pub mod cassette;
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod booking;
 pub mod client;
+pub mod country;
+pub mod courier;
+pub mod document;
 pub mod error;
+pub mod locations;
+pub mod logging;
+pub mod money;
 pub mod order;
+pub mod packing;
 pub mod product;
+pub mod ranking;
+pub mod retry;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tracking;
+pub mod transport;
+pub mod units;
+pub mod validate;
 
 type CommonUnsigned = u32;
 type CommonFloat    = f64;
+type CommonMoney    = CommonFloat;
 
 pub type Account = account::Account;
+pub type AccountKind = account::AccountKind;
+pub type AustralianState = account::AustralianState;
 pub type AuthenticateWith<'a> = account::AuthenticateWith<'a>;
-pub type Member = account::Member;
+pub type Member = account::Member<CommonMoney>;
+pub type MemberSettings = account::MemberSettings;
 
 pub type BookingStatus = booking::BookingStatus;
-pub type BookingRequest<'a> = booking::BookingRequest<'a, CommonUnsigned, CommonFloat>;
-pub type BookingResponse = booking::BookingResponse<CommonUnsigned, CommonFloat>;
+pub type BookingConfirmation = booking::BookingConfirmation;
+pub type BookingQuery = booking::BookingQuery;
+pub type BookingRequest<'a> = booking::BookingRequest<'a, CommonUnsigned, CommonFloat, CommonMoney>;
+pub type BookingRequestBuilder<'a> = booking::BookingRequestBuilder<'a, CommonUnsigned, CommonFloat, CommonMoney>;
+pub type BuildError = booking::BuildError;
+pub type BookingResponse = booking::BookingResponse<CommonUnsigned, CommonFloat, CommonMoney>;
+pub type Quotes = booking::Quotes<CommonMoney>;
+
+pub type Country = country::Country;
+pub type Courier = courier::Courier;
 
 pub type TransdirectClient<'a> = client::Client<'a>;
+pub type ClientBuilder<'a> = client::ClientBuilder<'a>;
+pub type Environment = client::Environment;
+#[cfg(feature = "async")]
+pub type TransdirectAsyncClient<'a> = async_client::AsyncClient<'a>;
+
+pub type Location = locations::Location;
+
+pub type Document = document::Document;
+pub type DocumentKind = document::DocumentKind;
+
+#[cfg(feature = "decimal")]
+pub type Money = money::Money;
+
+pub type Ranker<'s> = ranking::Ranker<'s, CommonMoney>;
+
+pub type RetryPolicy = retry::RetryPolicy;
 
 pub type Error = error::Error;
+pub type ApiError = error::ApiError;
 
 pub type OrderStatus = order::OrderStatus;
-// Missing Order
+pub type Order = order::Order<CommonUnsigned, CommonFloat, CommonMoney>;
+pub type OrderQuery = order::OrderQuery;
 
+pub type Consignment = product::Consignment<CommonFloat>;
 pub type Dimensions = product::Dimensions<CommonFloat>;
 pub type Product = product::Product<CommonUnsigned, CommonFloat>;
-pub type Service = product::Service<CommonFloat>;
+pub type Service = product::Service<CommonMoney>;
+pub type WeightRules = product::WeightRules<CommonFloat>;
+
+pub type LengthUnit = units::LengthUnit;
+pub type WeightUnit = units::WeightUnit;
+
+pub type TrackingEvent = tracking::TrackingEvent;
+pub type TrackingStatus = tracking::TrackingStatus;
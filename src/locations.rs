@@ -0,0 +1,119 @@
+use serde_derive::{Deserialize, Serialize};
+use serde::de::{self, Deserialize as _};
+
+use crate::Error;
+use crate::account::{Account, AustralianState};
+use crate::client::RestPath;
+use crate::country::Country;
+
+/// A suburb and its postcode, as matched by a location search
+///
+/// Can be copied onto an [`Account`] with [`Location::fill`], or turned into
+/// one with `Account::from`.
+///
+/// # Examples
+///
+/// ```
+/// use transdirect::{Account, AustralianState, Country};
+/// use transdirect::locations::Location;
+///
+/// let location = Location {
+///     suburb: "Mosman".to_string(),
+///     postcode: "2088".to_string(),
+///     state: AustralianState::NewSouthWales,
+///     country: Country::AU,
+/// };
+/// let mut receiver = Account { name: "Jane Doe".to_string(), ..Account::default() };
+/// location.fill(&mut receiver);
+///
+/// assert_eq!(receiver.postcode, "2088");
+/// assert_eq!(receiver.name, "Jane Doe");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(alias = "locality", alias = "town")]
+    pub suburb: String,
+    #[serde(deserialize_with = "deserialize_postcode")]
+    pub postcode: String,
+    pub state: AustralianState,
+    #[serde(default)]
+    pub country: Country, // Australia unless given
+}
+
+impl Location {
+    /// Sets the suburb, postcode, state and country of an account, leaving
+    /// everything else as is
+    pub fn fill(&self, account: &mut Account) {
+        account.suburb = self.suburb.clone();
+        account.postcode = self.postcode.clone();
+        account.state = self.state.clone();
+        account.country = self.country.clone();
+    }
+}
+
+impl From<Location> for Account {
+    fn from(location: Location) -> Self {
+        Account {
+            suburb: location.suburb,
+            postcode: location.postcode,
+            state: location.state,
+            country: location.country,
+            ..Account::default()
+        }
+    }
+}
+
+// Postcodes are sometimes sent as numbers, losing the leading zero of
+// Northern Territory and some ACT postcodes
+pub(crate) fn deserialize_postcode<'de, D>(deserializer: D) -> Result<String, D::Error>
+where D: de::Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Postcode {
+        Text(String),
+        Number(u32),
+    }
+
+    Ok(match Postcode::deserialize(deserializer)? {
+        Postcode::Text(postcode) => postcode,
+        Postcode::Number(postcode) => format!("{postcode:04}"),
+    })
+}
+
+// The locations are either returned bare or wrapped in an object
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum LocationGroup {
+    Bare(Vec<Location>),
+    Wrapped { locations: Vec<Location> },
+}
+
+impl LocationGroup {
+    pub(crate) fn into_locations(self) -> Vec<Location> {
+        match self {
+            Self::Bare(locations) | Self::Wrapped { locations } => locations,
+        }
+    }
+}
+
+impl RestPath<()> for LocationGroup {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("locations".to_string()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_numeric_postcodes() {
+        let group: LocationGroup = serde_json::from_str(r#"{ "locations": [
+            { "locality": "Darwin", "postcode": 800, "state": "NT" }
+        ]}"#).unwrap();
+        let locations = group.into_locations();
+
+        assert_eq!(locations[0].postcode, "0800");
+        assert_eq!(locations[0].state, AustralianState::NorthernTerritory);
+        assert_eq!(locations[0].country, Country::AU);
+    }
+}
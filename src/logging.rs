@@ -0,0 +1,253 @@
+//! Tracing of API calls
+//! 
+//! Every call made by a client runs inside a `transdirect_request` span
+//! recording its method, path, booking id (where there is one), status and
+//! latency. With `ClientBuilder::log_bodies`, the request headers and bodies
+//! and the responses are also logged at `DEBUG` level, with credentials
+//! redacted. Failures are logged at `WARN` level, with the body of an error
+//! response only under `log_bodies`. Nothing is logged unless a `tracing`
+//! subscriber is installed.
+use std::borrow::Cow;
+use std::time::Instant;
+
+use serde_json::Value;
+use tracing::{field, Span};
+
+use crate::Error;
+use crate::transport::{Request, Response};
+
+const REDACTED: &str = "[REDACTED]";
+const MAX_LOGGED_LEN: usize = 4096;
+
+// Body fields which are never logged, compared case-insensitively
+const SENSITIVE_FIELDS: [&str; 5] = ["password", "api_key", "apikey", "api-key", "token"];
+
+/// Redacts credentials from a header value: the whole `Api-key`, and
+/// everything after the scheme of an `Authorization` header
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::logging::redact_header;
+/// 
+/// assert_eq!(redact_header("Authorization", "Basic dXNlcjpwYXNz"), "Basic [REDACTED]");
+/// assert_eq!(redact_header("Api-key", "0123abcd"), "[REDACTED]");
+/// assert_eq!(redact_header("User-Agent", "warehouse/1.0"), "warehouse/1.0");
+/// ```
+pub fn redact_header<'a>(name: &str, value: &'a str) -> Cow<'a, str> {
+    if name.eq_ignore_ascii_case("api-key") {
+        Cow::Borrowed(REDACTED)
+    } else if name.eq_ignore_ascii_case("authorization") {
+        match value.split_once(' ') {
+            Some((scheme, _)) => Cow::Owned(format!("{scheme} {REDACTED}")),
+            None => Cow::Borrowed(REDACTED),
+        }
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Redacts credential fields (passwords, API keys, tokens) anywhere in a
+/// JSON body. Bodies which are not JSON are returned unchanged.
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        },
+        Err(_) => body.to_string(),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SENSITIVE_FIELDS.iter().any(|f| key.eq_ignore_ascii_case(f)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        },
+        Value::Array(values) => values.iter_mut().for_each(redact_value),
+        _ => {},
+    }
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_LOGGED_LEN {
+        let mut end = MAX_LOGGED_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("...");
+    }
+    text
+}
+
+/// A single traced call to the API
+pub(crate) struct ApiCall {
+    span: Span,
+    start: Instant,
+    log_bodies: bool,
+}
+
+impl ApiCall {
+    pub(crate) fn new(method: &str, path: &str, log_bodies: bool) -> Self {
+        let span = tracing::info_span!(
+            "transdirect_request",
+            method,
+            path,
+            booking_id = field::Empty,
+            status = field::Empty,
+            latency_ms = field::Empty,
+        );
+
+        Self { span, start: Instant::now(), log_bodies }
+    }
+
+    pub(crate) fn booking(self, booking_id: Option<u32>) -> Self {
+        if let Some(booking_id) = booking_id {
+            self.span.record("booking_id", booking_id);
+        }
+        self
+    }
+
+    /// Logs the outgoing headers and body, if bodies are being logged
+    pub(crate) fn request(self, request: &Request) -> Self {
+        if self.log_bodies {
+            let headers: Vec<String> = request.headers
+                .iter()
+                .map(|(name, value)| format!("{name}: {}", redact_header(name, value)))
+                .collect();
+            let body = request.body
+                .as_deref()
+                .map(|b| truncate(redact_body(&String::from_utf8_lossy(b))))
+                .unwrap_or_default();
+
+            self.span.in_scope(|| tracing::debug!(?headers, %body, "request"));
+        }
+        self
+    }
+
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Records the status and latency of the call, and logs its outcome
+    /// along with the response body, if bodies are being logged
+    pub(crate) fn finish(&self, result: Result<&Response, &Error>) {
+        let latency_ms = self.start.elapsed().as_millis() as u64;
+        self.span.record("latency_ms", latency_ms);
+
+        let _entered = self.span.enter();
+        match result {
+            Ok(response) => {
+                self.span.record("status", response.status);
+
+                if !self.log_bodies {
+                    tracing::debug!(latency_ms, "response");
+                } else if std::str::from_utf8(&response.body).is_ok() {
+                    let body = truncate(redact_body(&response.text()));
+                    tracing::debug!(latency_ms, %body, "response");
+                } else {
+                    tracing::debug!(latency_ms, bytes = response.body.len(), "response"); // Documents
+                }
+            },
+            // The error's message would include the response body
+            Err(Error::HTTPError { status, body, .. }) => {
+                self.span.record("status", status);
+
+                if self.log_bodies {
+                    let body = truncate(redact_body(body));
+                    tracing::warn!(latency_ms, status, %body, "request failed");
+                } else {
+                    tracing::warn!(latency_ms, status, "request failed");
+                }
+            },
+            Err(err) => tracing::warn!(latency_ms, error = %err, "request failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // Collects every field of every span and event, one per line
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<String>>);
+
+    impl field::Visit for Capture {
+        fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+            self.0.lock().unwrap().push_str(&format!("{}={value:?}\n", field.name()));
+        }
+    }
+
+    impl tracing::Subscriber for Capture {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            span.record(&mut self.clone());
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    /// Everything logged while running `f`
+    pub(crate) fn captured(f: impl FnOnce()) -> String {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), f);
+        let logged = capture.0.lock().unwrap().clone();
+        logged
+    }
+
+    #[test]
+    fn should_redact_nested_credentials() {
+        let body = redact_body(r#"{"member":{"Password":"hunter2","name":"Jo"},"keys":[{"api_key":"abc"}]}"#);
+
+        assert!(!body.contains("hunter2"));
+        assert!(!body.contains("abc"));
+        assert!(body.contains(r#""name":"Jo""#));
+    }
+
+    #[test]
+    fn should_only_log_error_bodies_when_asked() {
+        let err = Response { status: 422, body: br#"{"message":"Receiver jo@example.com is invalid"}"#.to_vec(), ..Response::default() }
+            .error_for_status()
+            .unwrap_err();
+
+        let quiet = captured(|| ApiCall::new("POST", "bookings/v4", false).finish(Err(&err)));
+        assert!(quiet.contains("status=422"));
+        assert!(!quiet.contains("jo@example.com"));
+
+        let loud = captured(|| ApiCall::new("POST", "bookings/v4", true).finish(Err(&err)));
+        assert!(loud.contains("jo@example.com"));
+    }
+
+    #[test]
+    fn should_truncate_on_char_boundary() {
+        let text = truncate("é".repeat(MAX_LOGGED_LEN));
+
+        assert!(text.ends_with("..."));
+        assert!(text.len() <= MAX_LOGGED_LEN + 3);
+    }
+}
@@ -0,0 +1,277 @@
+//! Monetary amounts
+//! 
+//! Every price and value in the API is generic over [`Amount`], which is
+//! implemented for `f32` and `f64`. With the `decimal` feature, [`Money`]
+//! offers an exact decimal alternative for reconciling invoices. The top-level
+//! aliases keep using `f64`; name the generic types to use `Money` instead:
+//! 
+//! ```ignore
+//! use transdirect::{booking, Money};
+//! 
+//! type BookingResponse = booking::BookingResponse<u32, f64, Money>;
+//! ```
+use std::fmt::Debug;
+
+/// A type which can hold a monetary amount
+pub trait Amount: Copy + PartialOrd + Default + Debug {
+    /// The amount as a float, for weighing amounts against one another
+    fn as_f64(self) -> f64;
+}
+
+impl Amount for f32 {
+    fn as_f64(self) -> f64 {
+        self.into()
+    }
+}
+
+impl Amount for f64 {
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(feature = "decimal")]
+pub use self::decimal::Money;
+
+#[cfg(feature = "decimal")]
+mod decimal {
+    use std::fmt;
+    use std::iter::Sum;
+    use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+    use std::str::FromStr;
+
+    use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+    use rust_decimal::{Decimal, RoundingStrategy};
+    use serde::{de, ser};
+
+    use super::Amount;
+
+    /// An exact amount of Australian dollars
+    /// 
+    /// Deserializes from either JSON numbers or strings (`18.5` or `"18.50"`)
+    /// and serializes as a number. Numbers are read through their shortest
+    /// decimal representation, so `18.1` becomes exactly 18.1 rather than the
+    /// nearest binary float.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::money::Money;
+    /// 
+    /// let total = Money::from_cents(2200);
+    /// 
+    /// assert_eq!(total.without_gst(), Money::from_cents(2000));
+    /// assert_eq!(total.gst(), Money::from_cents(200));
+    /// assert_eq!(Money::from_cents(2000).with_gst(), total);
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct Money(Decimal);
+
+    impl Money {
+        pub const ZERO: Money = Money(Decimal::ZERO);
+
+        pub fn new(amount: Decimal) -> Self {
+            Self(amount)
+        }
+
+        pub fn from_cents(cents: i64) -> Self {
+            Self(Decimal::new(cents, 2))
+        }
+
+        pub fn amount(&self) -> Decimal {
+            self.0
+        }
+
+        /// Rounds to whole cents, with halves rounded away from zero
+        pub fn round(self) -> Self {
+            Self(self.0.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero))
+        }
+
+        /// The GST-inclusive amount for a GST-exclusive one, to the cent
+        pub fn with_gst(self) -> Self {
+            Self(self.0 * (Decimal::ONE + gst_rate())).round()
+        }
+
+        /// The GST-exclusive amount for a GST-inclusive one, to the cent
+        pub fn without_gst(self) -> Self {
+            Self(self.0 / (Decimal::ONE + gst_rate())).round()
+        }
+
+        /// The GST included in a GST-inclusive amount
+        pub fn gst(self) -> Self {
+            self - self.without_gst()
+        }
+    }
+
+    // Australian GST is a flat 10%
+    fn gst_rate() -> Decimal {
+        Decimal::new(1, 1)
+    }
+
+    impl Amount for Money {
+        fn as_f64(self) -> f64 {
+            self.0.to_f64().unwrap_or(f64::NAN)
+        }
+    }
+
+    impl From<Decimal> for Money {
+        fn from(amount: Decimal) -> Self {
+            Self(amount)
+        }
+    }
+
+    impl FromStr for Money {
+        type Err = rust_decimal::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Decimal::from_str(s.trim().trim_start_matches('$')).map(Self)
+        }
+    }
+
+    impl fmt::Display for Money {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:.2}", self.0)
+        }
+    }
+
+    impl Add for Money {
+        type Output = Money;
+
+        fn add(self, rhs: Money) -> Money {
+            Money(self.0 + rhs.0)
+        }
+    }
+
+    impl AddAssign for Money {
+        fn add_assign(&mut self, rhs: Money) {
+            self.0 += rhs.0;
+        }
+    }
+
+    impl Sub for Money {
+        type Output = Money;
+
+        fn sub(self, rhs: Money) -> Money {
+            Money(self.0 - rhs.0)
+        }
+    }
+
+    impl SubAssign for Money {
+        fn sub_assign(&mut self, rhs: Money) {
+            self.0 -= rhs.0;
+        }
+    }
+
+    impl Mul<Decimal> for Money {
+        type Output = Money;
+
+        fn mul(self, rhs: Decimal) -> Money {
+            Money(self.0 * rhs)
+        }
+    }
+
+    impl Mul<u32> for Money {
+        type Output = Money;
+
+        fn mul(self, rhs: u32) -> Money {
+            Money(self.0 * Decimal::from(rhs))
+        }
+    }
+
+    impl Sum for Money {
+        fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+            iter.fold(Money::ZERO, Add::add)
+        }
+    }
+
+    impl ser::Serialize for Money {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+        {
+            // Sent as a JSON number, which serde_json can only write from an
+            // f64 (its `arbitrary_precision` feature would break reading
+            // floats in untagged and flattened types throughout the crate).
+            // The shortest float representation prints amounts of up to 15
+            // significant digits back exactly; anything longer is refused
+            // rather than silently rounded.
+            let exact = self.0.to_f64().is_some_and(|f| Decimal::from_str(&f.to_string()) == Ok(self.0.normalize()));
+            if !exact {
+                return Err(ser::Error::custom(format!("{} cannot be sent exactly as a number", self.0)));
+            }
+
+            rust_decimal::serde::float::serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de> de::Deserialize<'de> for Money {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+        {
+            deserializer.deserialize_any(MoneyVisitor)
+        }
+    }
+
+    struct MoneyVisitor;
+
+    impl de::Visitor<'_> for MoneyVisitor {
+        type Value = Money;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an amount as a number or string")
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Money, E> {
+            Ok(Money(Decimal::from(v)))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Money, E> {
+            Ok(Money(Decimal::from(v)))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Money, E> {
+            Decimal::from_str(&v.to_string())
+                .ok()
+                .or_else(|| Decimal::from_f64(v))
+                .map(Money)
+                .ok_or_else(|| E::custom(format!("{v} is not a valid amount")))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Money, E> {
+            v.parse().map_err(|_| E::custom(format!("{v:?} is not a valid amount")))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn should_accept_numbers_and_strings() {
+            let amounts: Vec<Money> = serde_json::from_str(r#"[18.1, "18.10", 18, "$18.1"]"#).unwrap();
+
+            assert_eq!(amounts, [1810, 1810, 1800, 1810].map(Money::from_cents));
+            assert_eq!(serde_json::to_string(&amounts[0]).unwrap(), "18.1");
+        }
+
+        #[test]
+        fn should_round_trip_exactly() {
+            let total = "0.1".parse::<Money>().unwrap() + "0.2".parse().unwrap();
+            let json = serde_json::to_string(&total).unwrap();
+
+            assert_eq!(json, "0.3");
+            assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), total);
+            assert_eq!(serde_json::to_string(&Money::from_cents(1_234_567_890_123)).unwrap(), "12345678901.23");
+
+            let precise: Money = "0.1234567890123456789".parse().unwrap();
+            assert!(serde_json::to_string(&precise).is_err());
+        }
+
+        #[test]
+        fn should_sum_exactly() {
+            let total: Money = vec![Money::from_cents(10); 3].into_iter().sum();
+
+            assert_eq!(total, Money::from_cents(30));
+            assert_eq!(total.to_string(), "0.30");
+        }
+    }
+}
@@ -1,13 +1,23 @@
 use std::str::FromStr;
+use num_traits::{Float,Unsigned};
 use serde_derive::{Serialize,Deserialize};
+use serde::ser;
 
 use crate::Error;
+use crate::client::RestPath;
+use crate::account::Account;
+use crate::booking::BookingRequest;
+use crate::courier::Courier;
+use crate::money::Amount;
+use crate::product::Product;
+
 /// Enum describing the status of an order: for member to create a booking
 /// at a later date
 /// 
 /// Defined in the [transdirect API documentation](https://transdirectapiv4.docs.apiary.io/reference/orders/create-orders)
 #[non_exhaustive]
-#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
 pub enum OrderStatus {
     #[default]
     Pending,
@@ -16,6 +26,18 @@ pub enum OrderStatus {
     Cancelled,
 }
 
+impl OrderStatus {
+    /// The value used for this status by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending            => "pending",
+            Self::Booked             => "booked",
+            Self::ManuallyDispatched => "manually_dispatched",
+            Self::Cancelled          => "cancelled",
+        }
+    }
+}
+
 impl FromStr for OrderStatus {
     type Err = Error;
 
@@ -28,4 +50,179 @@ impl FromStr for OrderStatus {
             _ => Err(Self::Err::UnknownStatus)
         }
     }
-}
\ No newline at end of file
+}
+
+/// An order pushed into Transdirect to be booked at a later date
+/// 
+/// Fields which are assigned by the server (`id`, `transdirect_order_id`,
+/// timestamps) are left out when sending, so an `Order` built with
+/// [`Order::new`] can be created directly.
+/// 
+/// As defined by the [specification](https://transdirectapiv4.docs.apiary.io/reference/orders)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Order<T, U, M = U> where T: Unsigned, U: Float, M: Amount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transdirect_order_id: Option<u32>,
+    pub order_id: String, // The member's own reference, e.g. a shop order number
+    #[serde(default)]
+    pub goods_summary: String,
+    #[serde(default)]
+    pub goods_dump: String,
+    #[serde(default)]
+    pub imported_from: String,
+    #[serde(default, with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub purchased_time: Option<time::OffsetDateTime>,
+    #[serde(default)]
+    pub sale_price: M,
+    #[serde(default)]
+    pub declared_value: M,
+    #[serde(default)]
+    pub paid_price: M,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub courier_price: Option<M>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_courier: Option<Courier>,
+    pub items: Vec<Product<T, U>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<Account>, // Falls back to the member's default sender
+    pub receiver: Account,
+    #[serde(default)]
+    pub status: OrderStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connote: Option<String>,
+    #[serde(default, with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<time::OffsetDateTime>,
+    #[serde(default, with = "time::serde::iso8601::option", skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<time::OffsetDateTime>,
+}
+
+impl<T, U, M> Order<T, U, M>
+where T: Unsigned + Default, U: Float + Default, M: Amount {
+    /// Creates an empty pending `Order`
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::{Order, Product};
+    /// 
+    /// let order = Order {
+    ///     order_id: "WEB-1042".to_string(),
+    ///     goods_summary: "2x widgets".to_string(),
+    ///     paid_price: 40.0,
+    ///     items: vec![Product::new()],
+    ///     ..Order::new()
+    /// };
+    /// ```
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// Builds the quote request for an order, borrowing its sender and receiver
+/// 
+/// When the order has no sender, the request has none either and Transdirect
+/// falls back to the member's default sender.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::{BookingRequest, Order};
+/// 
+/// let order = Order { declared_value: 80.0, ..Order::new() };
+/// let request = BookingRequest::from(&order);
+/// 
+/// assert_eq!(request.declared_value, 80.0);
+/// ```
+impl<'a, T, U, M> From<&'a Order<T, U, M>> for BookingRequest<'a, T, U, M>
+where T: Unsigned + Clone + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    fn from(order: &'a Order<T, U, M>) -> Self {
+        BookingRequest {
+            declared_value: order.declared_value,
+            referrer: String::new(),
+            requesting_site: String::new(),
+            tailgate_pickup: false,
+            tailgate_delivery: false,
+            items: order.items.clone(),
+            sender: order.sender.as_ref(),
+            receiver: Some(&order.receiver),
+        }
+    }
+}
+
+impl<T, U, M> RestPath<()> for Order<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("orders".to_string()) }
+}
+
+impl<T, U, M> RestPath<u32> for Order<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(params: u32) -> Result<String, Error> {
+        Ok(format!("orders/{params}"))
+    }
+}
+
+/// Filters for listing orders; every unset filter is left out of the query
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::{OrderQuery, OrderStatus};
+/// 
+/// let query = OrderQuery {
+///     status: Some(OrderStatus::Pending),
+///     per_page: Some(50),
+///     ..OrderQuery::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OrderQuery {
+    pub since: Option<time::OffsetDateTime>,
+    pub status: Option<OrderStatus>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl OrderQuery {
+    /// The `(key, value)` pairs of the query string
+    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+
+        if let Some(since) = self.since {
+            let since = since
+                .format(&time::format_description::well_known::Iso8601::DEFAULT)
+                .expect("Any OffsetDateTime should be formattable as ISO-8601");
+            pairs.push(("since", since));
+        }
+        if let Some(status) = self.status {
+            pairs.push(("status", status.as_str().to_string()));
+        }
+        if let Some(page) = self.page {
+            pairs.push(("page", page.to_string()));
+        }
+        if let Some(per_page) = self.per_page {
+            pairs.push(("per_page", per_page.to_string()));
+        }
+
+        pairs
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct OrderGroup<T, U, M>(pub(crate) Vec<Order<T, U, M>>)
+where T: Unsigned, U: Float, M: Amount;
+
+impl<T, U, M> RestPath<()> for OrderGroup<T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("orders".to_string()) }
+}
+
+#[derive(Serialize)]
+pub(crate) struct OrderBulk<'a, T, U, M>(pub(crate) &'a [Order<T, U, M>])
+where T: Unsigned, U: Float, M: Amount;
+
+impl<T, U, M> RestPath<()> for OrderBulk<'_, T, U, M>
+where T: Unsigned, U: Float, M: Amount {
+    fn get_path(_: ()) -> Result<String, Error> { Ok("orders/bulk".to_string()) }
+}
@@ -0,0 +1,468 @@
+//! Packing line items into shipping cartons
+//!
+//! Transdirect quotes and books cartons, not the things inside them. Given
+//! the line items of an order and the carton sizes on hand, [`pack`] works
+//! out which cartons to use and what goes in each, so the result can be
+//! sent as the items of a [`BookingRequest`](crate::booking::BookingRequest).
+//!
+//! Packing is a heuristic (first fit decreasing, with guillotine cuts of
+//! the free space), so it is quick and good, but not always optimal.
+//!
+//! # Examples
+//!
+//! ```
+//! use transdirect::{Dimensions, Product};
+//! use transdirect::packing::{self, Carton, Item};
+//!
+//! let items = [
+//!     Item { sku: "MUG".to_string(), quantity: 6u32, weight: 0.5, dimensions: Dimensions::from_lwh(12.0, 9.0, 10.0) },
+//!     Item { sku: "TEAPOT".to_string(), quantity: 1, weight: 1.5, dimensions: Dimensions::from_lwh(25.0, 18.0, 20.0) },
+//! ];
+//! let cartons = [
+//!     Carton::new("small", Dimensions::from_lwh(30.0, 20.0, 20.0), 10.0),
+//!     Carton::new("large", Dimensions::from_lwh(40.0, 30.0, 30.0), 20.0),
+//! ];
+//!
+//! let packing = packing::pack(&items, &cartons).unwrap();
+//! assert_eq!(packing.cartons.len(), 1);
+//! assert_eq!(packing.cartons[0].carton.name, "large");
+//! assert_eq!(packing.cartons[0].counts()["MUG"], 6);
+//!
+//! let products: Vec<Product> = packing.products();
+//! assert_eq!(products[0].weight, 4.5);
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+
+use num_traits::{Float, ToPrimitive, Unsigned};
+
+use crate::product::{Dimensions, Product};
+
+/// A line item to be packed, e.g. one line of an order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item<T, U> where T: Unsigned, U: Float {
+    pub sku: String,
+    pub quantity: T,
+    pub weight: U, // kg, of each
+    pub dimensions: Dimensions<U>,
+}
+
+/// A carton size which items can be packed into
+///
+/// The wall thickness is not accounted for: the dimensions are taken as
+/// both the inside and the outside of the carton.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Carton<U> where U: Float {
+    pub name: String,
+    pub dimensions: Dimensions<U>,
+    pub max_weight: U, // kg, including the carton itself
+    pub weight: U, // kg, of the empty carton
+}
+
+impl<U> Carton<U> where U: Float {
+    /// Creates a carton weighing nothing when empty
+    pub fn new(name: impl Into<String>, dimensions: Dimensions<U>, max_weight: U) -> Self {
+        Carton {
+            name: name.into(),
+            dimensions,
+            max_weight,
+            weight: U::zero(),
+        }
+    }
+
+    /// Sets the weight of the empty carton, in kg
+    pub fn with_weight(mut self, weight: U) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Where one unit of an item was put in a carton
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement<U> where U: Float {
+    pub sku: String,
+    pub position: (U, U, U), // cm from a corner, along the carton's length, width and height
+    pub dimensions: Dimensions<U>, // As turned to fit
+}
+
+/// A carton and what was packed in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedCarton<U> where U: Float {
+    pub carton: Carton<U>,
+    pub contents: Vec<Placement<U>>,
+    pub weight: U, // kg, including the carton itself
+}
+
+impl<U> PackedCarton<U> where U: Float {
+    /// How many units of each item are in the carton, by SKU
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for placement in &self.contents {
+            *counts.entry(placement.sku.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The fraction of the carton's volume which is filled
+    pub fn utilisation(&self) -> U {
+        let used = self.contents.iter().fold(U::zero(), |total, p| total + p.dimensions.volume());
+        used / self.carton.dimensions.volume()
+    }
+}
+
+/// The cartons a set of items were packed into
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Packing<U> where U: Float {
+    pub cartons: Vec<PackedCarton<U>>,
+}
+
+impl<U> Packing<U> where U: Float {
+    /// One product for each packed carton, to be booked
+    ///
+    /// Each is described by the carton's name and its contents, e.g.
+    /// `"large: 6 × MUG, 1 × TEAPOT"`.
+    pub fn products<T>(&self) -> Vec<Product<T, U>> where T: Unsigned {
+        self.cartons
+            .iter()
+            .map(|packed| {
+                let contents: Vec<_> = packed
+                    .counts()
+                    .into_iter()
+                    .map(|(sku, count)| format!("{count} × {sku}"))
+                    .collect();
+
+                Product {
+                    quantity: T::one(),
+                    weight: packed.weight,
+                    dimensions: packed.carton.dimensions,
+                    description: format!("{}: {}", packed.carton.name, contents.join(", ")),
+                    id: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The most units, across all items, which [`pack`] will pack at once
+///
+/// Every unit is placed individually, so packing takes time and memory
+/// growing faster than the number of units. Larger orders are better split
+/// up, or packed by the pallet.
+pub const MAX_UNITS: usize = 2_000;
+
+/// Why items could not be packed
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    Unpackable(Vec<String>), // SKUs of items which fit in no carton
+    TooManyUnits(usize), // More than `MAX_UNITS`, saturating at `usize::MAX`
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unpackable(skus) => write!(f, "items fit in no carton: {}", skus.join(", ")),
+            Self::TooManyUnits(units) => write!(f, "{units} units is more than the {MAX_UNITS} which can be packed at once"),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+/// Packs items into as few cartons as the heuristic can find, then swaps
+/// each carton for the smallest one its contents still fit in
+///
+/// Returns [`PackError::Unpackable`] with the SKUs of any items which fit in
+/// none of the cartons, however they are turned, by size or by weight, and
+/// [`PackError::TooManyUnits`] if the quantities add up to more than
+/// [`MAX_UNITS`].
+pub fn pack<T, U>(items: &[Item<T, U>], cartons: &[Carton<U>]) -> Result<Packing<U>, PackError>
+where T: Unsigned + ToPrimitive + Copy, U: Float {
+    let unpackable: Vec<String> = items
+        .iter()
+        .filter(|item| !cartons.iter().any(|carton| fits_alone(&item.dimensions, item.weight, carton)))
+        .map(|item| item.sku.clone())
+        .collect();
+    if !unpackable.is_empty() {
+        return Err(PackError::Unpackable(unpackable));
+    }
+
+    let total = items
+        .iter()
+        .map(|item| item.quantity.to_usize().unwrap_or(usize::MAX))
+        .fold(0usize, usize::saturating_add);
+    if total > MAX_UNITS {
+        return Err(PackError::TooManyUnits(total));
+    }
+
+    let mut units: Vec<Unit<U>> = items
+        .iter()
+        .flat_map(|item| {
+            let count = item.quantity.to_usize().unwrap_or(0);
+            std::iter::repeat_n(Unit { sku: &item.sku, weight: item.weight, dimensions: item.dimensions }, count)
+        })
+        .collect();
+    sort_largest_first(&mut units);
+
+    // Smallest first, so that ties go to the smaller carton
+    let mut by_volume: Vec<&Carton<U>> = cartons.iter().collect();
+    by_volume.sort_by(|a, b| a.dimensions.volume().partial_cmp(&b.dimensions.volume()).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut packed = Vec::new();
+    while let Some(first) = units.first() {
+        // Whichever carton takes the most of what is left
+        let (open, placed) = by_volume
+            .iter()
+            .filter(|carton| fits_alone(&first.dimensions, first.weight, carton))
+            .map(|carton| fill(carton, &units))
+            .fold(None, |best: Option<(Open<U>, Vec<bool>)>, candidate| match best {
+                Some(best) if best.0.used >= candidate.0.used => Some(best),
+                _ => Some(candidate),
+            })
+            .expect("Every item fits in some carton");
+
+        let mut placed = placed.into_iter();
+        units.retain(|_| !placed.next().unwrap_or(false));
+        packed.push(open);
+    }
+
+    let cartons = packed
+        .into_iter()
+        .map(|open| downsize(open, &by_volume).into_packed())
+        .collect();
+    Ok(Packing { cartons })
+}
+
+// A single unit of an item, waiting to be packed
+#[derive(Clone)]
+struct Unit<'a, U> where U: Float {
+    sku: &'a str,
+    weight: U,
+    dimensions: Dimensions<U>,
+}
+
+// An empty cuboid within a carton
+#[derive(Clone)]
+struct Space<U> {
+    position: (U, U, U),
+    size: (U, U, U),
+}
+
+// A carton being packed
+#[derive(Clone)]
+struct Open<'c, U> where U: Float {
+    carton: &'c Carton<U>,
+    spaces: Vec<Space<U>>,
+    contents: Vec<Placement<U>>,
+    weight: U,
+    used: U, // cm³
+}
+
+impl<'c, U> Open<'c, U> where U: Float {
+    fn new(carton: &'c Carton<U>) -> Self {
+        let d = carton.dimensions;
+        Open {
+            carton,
+            spaces: vec![Space { position: (U::zero(), U::zero(), U::zero()), size: (d.length, d.width, d.height) }],
+            contents: Vec::new(),
+            weight: carton.weight,
+            used: U::zero(),
+        }
+    }
+
+    // Puts the unit in the free space it fills best, if there is one
+    fn place(&mut self, unit: &Unit<U>) -> bool {
+        if self.weight + unit.weight > self.carton.max_weight + tolerance() {
+            return false;
+        }
+
+        let volume = unit.dimensions.volume();
+        let best = self
+            .spaces
+            .iter()
+            .enumerate()
+            .flat_map(|(i, space)| orientations(&unit.dimensions).into_iter().map(move |turned| (i, space, turned)))
+            .filter(|(_, space, turned)| within(*turned, space.size))
+            .map(|(i, space, turned)| (i, turned, space.size.0 * space.size.1 * space.size.2 - volume))
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+        let Some((i, (l, w, h), _)) = best else {
+            return false;
+        };
+
+        let Space { position: (x, y, z), size: (sl, sw, sh) } = self.spaces.swap_remove(i);
+        self.spaces.extend(
+            [
+                Space { position: (x + l, y, z), size: (sl - l, sw, sh) },
+                Space { position: (x, y + w, z), size: (l, sw - w, sh) },
+                Space { position: (x, y, z + h), size: (l, w, sh - h) },
+            ]
+            .into_iter()
+            .filter(|space| [space.size.0, space.size.1, space.size.2].iter().all(|&side| side > tolerance())),
+        );
+
+        self.contents.push(Placement {
+            sku: unit.sku.to_string(),
+            position: (x, y, z),
+            dimensions: Dimensions { length: l, width: w, height: h },
+        });
+        self.weight = self.weight + unit.weight;
+        self.used = self.used + volume;
+        true
+    }
+
+    fn into_packed(self) -> PackedCarton<U> {
+        PackedCarton {
+            carton: self.carton.clone(),
+            contents: self.contents,
+            weight: self.weight,
+        }
+    }
+}
+
+// Packs as many of the units as will go into one carton, marking which
+fn fill<'c, U>(carton: &'c Carton<U>, units: &[Unit<U>]) -> (Open<'c, U>, Vec<bool>) where U: Float {
+    let mut open = Open::new(carton);
+    let placed = units.iter().map(|unit| open.place(unit)).collect();
+    (open, placed)
+}
+
+// Repacks a carton's contents into the smallest carton they all fit in
+fn downsize<'c, U>(open: Open<'c, U>, by_volume: &[&'c Carton<U>]) -> Open<'c, U> where U: Float {
+    // Weight does not depend on placement, so is only checked in total
+    let contents_weight = open.weight - open.carton.weight;
+    let mut units: Vec<Unit<U>> = open
+        .contents
+        .iter()
+        .map(|p| Unit { sku: &p.sku, weight: U::zero(), dimensions: p.dimensions })
+        .collect();
+    sort_largest_first(&mut units);
+
+    let smaller = by_volume
+        .iter()
+        .take_while(|carton| carton.dimensions.volume() < open.carton.dimensions.volume())
+        .filter(|carton| carton.weight + contents_weight <= carton.max_weight + tolerance())
+        .map(|carton| fill(carton, &units))
+        .find(|(_, placed)| placed.iter().all(|&p| p));
+
+    match smaller {
+        Some((mut smaller, _)) => {
+            smaller.weight = smaller.carton.weight + contents_weight;
+            smaller
+        },
+        None => open,
+    }
+}
+
+fn fits_alone<U>(dimensions: &Dimensions<U>, weight: U, carton: &Carton<U>) -> bool where U: Float {
+    let d = carton.dimensions;
+    carton.weight + weight <= carton.max_weight + tolerance()
+        && orientations(dimensions).into_iter().any(|turned| within(turned, (d.length, d.width, d.height)))
+}
+
+fn within<U>((l, w, h): (U, U, U), (sl, sw, sh): (U, U, U)) -> bool where U: Float {
+    l <= sl + tolerance() && w <= sw + tolerance() && h <= sh + tolerance()
+}
+
+// Every way an item can be turned, as length, width and height
+fn orientations<U>(d: &Dimensions<U>) -> [(U, U, U); 6] where U: Float {
+    let (l, w, h) = (d.length, d.width, d.height);
+    [(l, w, h), (l, h, w), (w, l, h), (w, h, l), (h, l, w), (h, w, l)]
+}
+
+fn sort_largest_first<U>(units: &mut [Unit<U>]) where U: Float {
+    units.sort_by(|a, b| {
+        (b.dimensions.volume(), b.weight)
+            .partial_cmp(&(a.dimensions.volume(), a.weight))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+// Allows for floating point error when comparing sizes and weights
+fn tolerance<U>() -> U where U: Float {
+    U::from(1e-6).unwrap_or_else(U::epsilon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(sku: &str, quantity: u32, weight: f64, l: f64, w: f64, h: f64) -> Item<u32, f64> {
+        Item { sku: sku.to_string(), quantity, weight, dimensions: Dimensions::from_lwh(l, w, h) }
+    }
+
+    fn overlaps(a: &Placement<f64>, b: &Placement<f64>) -> bool {
+        let apart = |a0: f64, al: f64, b0: f64, bl: f64| a0 + al <= b0 + 1e-6 || b0 + bl <= a0 + 1e-6;
+        !(apart(a.position.0, a.dimensions.length, b.position.0, b.dimensions.length)
+            || apart(a.position.1, a.dimensions.width, b.position.1, b.dimensions.width)
+            || apart(a.position.2, a.dimensions.height, b.position.2, b.dimensions.height))
+    }
+
+    #[test]
+    fn should_pack_every_unit_without_overlap() {
+        let items = [
+            item("BOOK", 7, 0.8, 24.0, 16.0, 4.0),
+            item("LAMP", 2, 2.5, 30.0, 30.0, 45.0),
+            item("CABLE", 11, 0.1, 10.0, 10.0, 3.0),
+        ];
+        let cartons = [
+            Carton::new("satchel", Dimensions::from_lwh(35.0, 25.0, 8.0), 3.0),
+            Carton::new("cube", Dimensions::from_lwh(50.0, 50.0, 50.0), 22.0).with_weight(0.5),
+        ];
+
+        let packing = pack(&items, &cartons).unwrap();
+        let mut counts = BTreeMap::new();
+        for packed in &packing.cartons {
+            let d = packed.carton.dimensions;
+            for (i, a) in packed.contents.iter().enumerate() {
+                assert!(within((a.position.0 + a.dimensions.length, a.position.1 + a.dimensions.width,
+                    a.position.2 + a.dimensions.height), (d.length, d.width, d.height)));
+                assert!(packed.contents[i + 1..].iter().all(|b| !overlaps(a, b)));
+            }
+            assert!(packed.weight <= packed.carton.max_weight);
+            for (sku, count) in packed.counts() {
+                *counts.entry(sku).or_insert(0) += count;
+            }
+        }
+
+        assert_eq!(counts, BTreeMap::from([("BOOK", 7), ("CABLE", 11), ("LAMP", 2)]));
+    }
+
+    #[test]
+    fn should_split_by_weight_and_use_smallest_carton() {
+        let items = [item("WEIGHT", 3, 9.0, 10.0, 10.0, 10.0)];
+        let cartons = [
+            Carton::new("large", Dimensions::from_lwh(60.0, 60.0, 60.0), 20.0),
+            Carton::new("small", Dimensions::from_lwh(20.0, 20.0, 20.0), 20.0),
+        ];
+
+        let packing = pack(&items, &cartons).unwrap();
+        let products: Vec<Product<u32, f64>> = packing.products();
+
+        assert_eq!(products.len(), 2);
+        assert_eq!(products[0].description, "small: 2 × WEIGHT");
+        assert_eq!(products[0].weight, 18.0);
+        assert_eq!(products[1].description, "small: 1 × WEIGHT");
+    }
+
+    #[test]
+    fn should_reject_items_fitting_no_carton() {
+        let items = [item("POLE", 1, 1.0, 200.0, 5.0, 5.0), item("ANVIL", 1, 50.0, 10.0, 10.0, 10.0), item("OK", 1, 1.0, 1.0, 1.0, 1.0)];
+        let cartons = [Carton::new("box", Dimensions::from_lwh(50.0, 50.0, 50.0), 25.0)];
+
+        match pack(&items, &cartons) {
+            Err(PackError::Unpackable(skus)) => assert_eq!(skus, ["POLE", "ANVIL"]),
+            other => panic!("Expected unpackable items, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn should_refuse_too_many_units() {
+        let cartons = [Carton::new("box", Dimensions::from_lwh(50.0, 50.0, 50.0), 25.0)];
+
+        let items = [item("PIN", 1_500, 0.001, 1.0, 1.0, 1.0), item("CLIP", 501, 0.001, 1.0, 1.0, 1.0)];
+        assert_eq!(pack(&items, &cartons), Err(PackError::TooManyUnits(2_001)));
+
+        let items = [item("PIN", u32::MAX, 0.001, 1.0, 1.0, 1.0)];
+        assert_eq!(pack(&items, &cartons), Err(PackError::TooManyUnits(u32::MAX as usize)));
+    }
+}
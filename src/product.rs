@@ -3,14 +3,17 @@
 /// 
 use std::default::Default;
 use std::collections::HashMap;
-use num_traits::{Float,Unsigned};
+use num_traits::{Float,ToPrimitive,Unsigned};
 use serde_derive::{Serialize,Deserialize};
 use serde::ser;
 
+use crate::money::Amount;
+use crate::units::{LengthUnit, WeightUnit};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct Product<T, U> where T: Unsigned, U: Float {
     pub quantity: T,
-    pub weight: U, // Transdirect calculates weight in increments of 1kg
+    pub weight: U, // In kg, of each item. Transdirect calculates weight in increments of 1kg
     #[serde(flatten)]
     pub dimensions: Dimensions<U>,
     pub description: String,
@@ -41,6 +44,36 @@ where T: Unsigned + ser::Serialize + Default, U: Float + ser::Serialize + Defaul
     }
 }
 
+impl<T, U> Product<T, U> where T: Unsigned, U: Float {
+    /// Sets the weight of each item from a weight in any unit
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::{Dimensions, LengthUnit, Product, WeightUnit};
+    /// 
+    /// // From a catalogue in millimetres and grams
+    /// let item = Product {
+    ///     quantity: 1,
+    ///     dimensions: Dimensions::from_lwh_in(LengthUnit::Millimetre, 300.0, 200.0, 150.0),
+    ///     ..Product::new()
+    /// }.with_weight(WeightUnit::Gram, 850.0);
+    /// 
+    /// assert_eq!(item.weight, 0.85);
+    /// assert_eq!(item.dimensions, Dimensions::from_lwh(30.0, 20.0, 15.0));
+    /// ```
+    pub fn with_weight(mut self, unit: WeightUnit, weight: U) -> Self {
+        self.weight = unit.to_kg(weight);
+        self
+    }
+
+    /// The weight of each item, in any unit
+    pub fn weight_in(&self, unit: WeightUnit) -> U {
+        unit.from_kg(self.weight)
+    }
+}
+
+/// The outside dimensions of an item, in cm
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Default)]
 pub struct Dimensions<T> where T: Float {
     pub length: T,
@@ -53,6 +86,7 @@ impl<T> Dimensions<T> where T: Float + Default {
         Default::default()
     }
     
+    /// Creates dimensions from a length, width and height in cm
     pub fn from_lwh(length: T, width: T, height: T) -> Self {
         Dimensions {
             length,
@@ -60,6 +94,170 @@ impl<T> Dimensions<T> where T: Float + Default {
             height,
         }
     }
+
+    /// Creates dimensions from a length, width and height in any unit,
+    /// converting them to cm
+    pub fn from_lwh_in(unit: LengthUnit, length: T, width: T, height: T) -> Self {
+        Dimensions {
+            length: unit.to_cm(length),
+            width: unit.to_cm(width),
+            height: unit.to_cm(height),
+        }
+    }
+}
+
+impl<T> Dimensions<T> where T: Float {
+    /// The length, width and height in any unit
+    pub fn to_lwh_in(&self, unit: LengthUnit) -> (T, T, T) {
+        (unit.from_cm(self.length), unit.from_cm(self.width), unit.from_cm(self.height))
+    }
+
+    /// The volume in cm³
+    pub fn volume(&self) -> T {
+        self.length * self.width * self.height
+    }
+
+    /// The volume in m³
+    pub fn volume_m3(&self) -> T {
+        self.volume() / cast(1_000_000.0)
+    }
+
+    /// The cubic (volumetric) weight in kg, for a courier's cubic factor in
+    /// kg/m³, e.g. [`DEFAULT_CUBIC_FACTOR`]
+    pub fn cubic_weight(&self, cubic_factor: T) -> T {
+        self.volume_m3() * cubic_factor
+    }
+}
+
+/// The cubic factor most Australian road couriers charge by, in kg/m³
+pub const DEFAULT_CUBIC_FACTOR: f64 = 250.0;
+
+/// How a courier works out the weight it charges for
+/// 
+/// Items are charged by the greater of their dead (actual) weight and their
+/// cubic weight, rounded up to the billing increment.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WeightRules<U> where U: Float {
+    pub cubic_factor: U, // kg/m³
+    pub increment: U, // kg
+}
+
+/// A cubic factor of 250 kg/m³, charged in whole kilograms as Transdirect
+/// does
+impl<U> Default for WeightRules<U> where U: Float {
+    fn default() -> Self {
+        Self {
+            cubic_factor: cast(DEFAULT_CUBIC_FACTOR),
+            increment: U::one(),
+        }
+    }
+}
+
+impl<U> WeightRules<U> where U: Float {
+    /// Rounds a weight up to the billing increment, forgiving the error of
+    /// floating point arithmetic
+    pub fn round_up(&self, weight: U) -> U {
+        if self.increment <= U::zero() {
+            return weight;
+        }
+
+        ((weight / self.increment) - cast(1e-9)).ceil().max(U::zero()) * self.increment
+    }
+}
+
+impl<T, U> Product<T, U> where T: Unsigned + ToPrimitive + Copy, U: Float {
+    /// The quantity as a float, for multiplying weights and volumes
+    fn count(&self) -> U {
+        U::from(self.quantity).unwrap_or_else(U::zero)
+    }
+
+    /// The volume of every item, in cm³
+    pub fn volume(&self) -> U {
+        self.dimensions.volume() * self.count()
+    }
+
+    /// The actual weight of every item, in kg
+    pub fn dead_weight(&self) -> U {
+        self.weight * self.count()
+    }
+
+    /// The cubic weight of every item, in kg
+    pub fn cubic_weight(&self, cubic_factor: U) -> U {
+        self.dimensions.cubic_weight(cubic_factor) * self.count()
+    }
+
+    /// The weight charged for every item, in kg: the greater of the dead and
+    /// cubic weights, rounded up to the billing increment
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::{Dimensions, Product};
+    /// use transdirect::product::WeightRules;
+    /// 
+    /// // A light but bulky box: 0.06 m³ is 15 kg at 250 kg/m³
+    /// let pillows = Product { quantity: 1, weight: 2.0, dimensions: Dimensions::from_lwh(50.0, 40.0, 30.0), ..Product::new() };
+    /// assert_eq!(pillows.chargeable_weight(&WeightRules::default()), 15.0);
+    /// 
+    /// let rules = WeightRules { cubic_factor: 200.0, increment: 0.5 };
+    /// assert_eq!(pillows.chargeable_weight(&rules), 12.0);
+    /// ```
+    pub fn chargeable_weight(&self, rules: &WeightRules<U>) -> U {
+        rules.round_up(self.unrounded_weight(rules))
+    }
+
+    fn unrounded_weight(&self, rules: &WeightRules<U>) -> U {
+        self.dead_weight().max(self.cubic_weight(rules.cubic_factor))
+    }
+}
+
+/// The totals of every item in a consignment
+/// 
+/// The chargeable weight is rounded once, for the whole consignment, which
+/// is what Transdirect returns as `charged_weight`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use transdirect::{Dimensions, Product};
+/// use transdirect::product::{Consignment, WeightRules};
+/// 
+/// let items = vec![
+///     Product { quantity: 2, weight: 3.2, dimensions: Dimensions::from_lwh(30.0, 20.0, 10.0), ..Product::new() },
+///     Product { quantity: 1, weight: 0.5, dimensions: Dimensions::from_lwh(60.0, 40.0, 40.0), ..Product::new() },
+/// ];
+/// let totals = Consignment::of(&items, &WeightRules::default());
+/// 
+/// assert_eq!(totals.items, 3);
+/// assert_eq!(totals.chargeable_weight, 31.0); // 6.4 kg dead + 24 kg cubic
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Consignment<U> where U: Float {
+    pub items: u64,
+    pub volume: U, // cm³
+    pub dead_weight: U, // kg
+    pub cubic_weight: U, // kg
+    pub chargeable_weight: U, // kg, rounded up to the billing increment
+}
+
+impl<U> Consignment<U> where U: Float {
+    pub fn of<T>(products: &[Product<T, U>], rules: &WeightRules<U>) -> Self
+    where T: Unsigned + ToPrimitive + Copy {
+        let sum = |f: &dyn Fn(&Product<T, U>) -> U| products.iter().fold(U::zero(), |total, p| total + f(p));
+
+        Self {
+            items: products.iter().filter_map(|p| p.quantity.to_u64()).sum(),
+            volume: sum(&|p| p.volume()),
+            dead_weight: sum(&|p| p.dead_weight()),
+            cubic_weight: sum(&|p| p.cubic_weight(rules.cubic_factor)),
+            chargeable_weight: rules.round_up(sum(&|p| p.unrounded_weight(rules))),
+        }
+    }
+}
+
+// Every float type can hold the constants used here
+fn cast<U>(n: f64) -> U where U: Float {
+    U::from(n).expect("Constant should be representable")
 }
 
 // impl<T> ser::Serialize for Dimensions<T> where T: Float + ser::Serialize {
@@ -76,8 +274,8 @@ impl<T> Dimensions<T> where T: Float + Default {
 /// A service provided by one of the companies listed by Transdirect.
 /// It is put in the products file because it is a product provided by
 /// external companies.
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Service<T> where T: Float {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Service<T> where T: Amount {
     pub total: T,
     pub price_insurance_ex: T,
     pub fee: T,
@@ -87,3 +285,72 @@ pub struct Service<T> where T: Float {
     pub pickup_dates: Vec<String>,
     pub pickup_time: HashMap<String, String>,
 }
+
+impl<T> Service<T> where T: Amount {
+    /// The longest transit time quoted, in (business) days
+    /// 
+    /// Couriers describe transit times loosely, e.g. `"1-2 days"`,
+    /// `"3 Business Days"`, `"Next day"` or `"Same day"`; `None` is returned
+    /// when no number of days can be made out.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::Service;
+    /// # let base = Service { total: 0.0, price_insurance_ex: 0.0, fee: 0.0, insured_amount: 0.0,
+    /// #     service: String::new(), transit_time: String::new(), pickup_dates: vec![], pickup_time: Default::default() };
+    /// 
+    /// let s = Service { transit_time: "2-4 Business Days".to_string(), ..base.clone() };
+    /// assert_eq!(s.transit_days(), Some(4));
+    /// 
+    /// let s = Service { transit_time: "Overnight".to_string(), ..base };
+    /// assert_eq!(s.transit_days(), Some(1));
+    /// ```
+    pub fn transit_days(&self) -> Option<u32> {
+        let text = self.transit_time.to_ascii_lowercase();
+
+        if text.contains("same day") || text.contains("sameday") {
+            return Some(0);
+        }
+        if text.contains("next day") || text.contains("overnight") {
+            return Some(1);
+        }
+
+        text
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|n| n.parse().ok())
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_up_to_increment_once() {
+        let rules = WeightRules { cubic_factor: 250.0, increment: 0.5 };
+        let item = Product {
+            quantity: 3u32,
+            weight: 1.1,
+            dimensions: Dimensions::from_lwh(10.0, 10.0, 10.0),
+            ..Product::new()
+        };
+
+        // 3.3 kg dead beats 0.75 kg cubic, and 3.3000000000000003 is still 3.5
+        assert_eq!(item.cubic_weight(rules.cubic_factor), 0.75);
+        assert_eq!(item.chargeable_weight(&rules), 3.5);
+
+        let totals = Consignment::of(&[item.clone(), item], &rules);
+        assert_eq!(totals.items, 6);
+        assert_eq!(totals.volume, 6000.0);
+        assert_eq!(totals.chargeable_weight, 7.0);
+    }
+
+    #[test]
+    fn should_total_nothing_for_empty_consignment() {
+        let totals = Consignment::<f64>::of::<u32>(&[], &WeightRules::default());
+
+        assert_eq!(totals, Consignment::default());
+    }
+}
@@ -0,0 +1,337 @@
+//! Choosing between the quotes for a booking
+//!
+//! A [`Ranker`] orders the [`Quotes`] of a [`BookingResponse`] by a
+//! [`Strategy`], best first, after leaving out any couriers which are not
+//! allowed. Each [`Ranked`] quote says why it scored as it did, so the
+//! choice can be shown to whoever is booking.
+//!
+//! The strategies provided are [`Cheapest`], [`Fastest`],
+//! [`EarliestPickup`] and [`BestValue`], which weighs all three; anything
+//! else can be ranked by implementing [`Strategy`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use transdirect::{BookingRequest, Courier, TransdirectClient as Client};
+//! use transdirect::ranking::{BestValue, Ranker};
+//! # let client = Client::new();
+//! # let request = BookingRequest::new();
+//!
+//! let response: transdirect::BookingResponse = client.quotes(&request)?;
+//! let ranked = Ranker::new(BestValue::default())
+//!     .deny([Courier::Fastway])
+//!     .rank(&response.quotes);
+//!
+//! for quote in &ranked {
+//!     println!("{}: {}", quote.courier, quote.reason);
+//! }
+//! # Ok::<(), transdirect::Error>(())
+//! ```
+//!
+//! [`BookingResponse`]: crate::booking::BookingResponse
+use std::collections::HashSet;
+
+use time::format_description::well_known::Iso8601;
+
+use crate::booking::Quotes;
+use crate::courier::Courier;
+use crate::money::Amount;
+use crate::product::Service;
+
+/// A way of scoring quotes, where the lowest score is the best
+pub trait Strategy<M> where M: Amount {
+    /// Scores a quote against all of the quotes being ranked, or leaves it
+    /// out of the ranking with `None`
+    fn score(&self, quote: &Quote<'_, M>, quotes: &[Quote<'_, M>]) -> Option<Score>;
+}
+
+/// The score given to a quote, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub value: f64,
+    pub reason: String,
+}
+
+/// A courier's quote, as seen by a [`Strategy`]
+#[derive(Debug)]
+pub struct Quote<'a, M> where M: Amount {
+    pub courier: &'a Courier,
+    pub service: &'a Service<M>,
+}
+
+impl<M> Quote<'_, M> where M: Amount {
+    /// The total price, as a float
+    pub fn total(&self) -> f64 {
+        self.service.total.as_f64()
+    }
+
+    /// The first of the quoted pickup dates which can be read
+    pub fn earliest_pickup(&self) -> Option<time::Date> {
+        self.service
+            .pickup_dates
+            .iter()
+            .filter_map(|date| time::Date::parse(date, &Iso8601::DATE).ok())
+            .min()
+    }
+}
+
+// Written by hand so that `M` need not be `Clone`
+impl<M> Clone for Quote<'_, M> where M: Amount {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for Quote<'_, M> where M: Amount {}
+
+/// A quote in its place in a ranking
+#[derive(Debug, Clone)]
+pub struct Ranked<'a, M> where M: Amount {
+    pub courier: &'a Courier,
+    pub service: &'a Service<M>,
+    pub score: f64,
+    pub reason: String,
+}
+
+/// Ranks quotes by the lowest total price
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Cheapest;
+
+impl<M> Strategy<M> for Cheapest where M: Amount {
+    fn score(&self, quote: &Quote<'_, M>, _: &[Quote<'_, M>]) -> Option<Score> {
+        Some(Score {
+            value: quote.total(),
+            reason: format!("${:.2} in total", quote.total()),
+        })
+    }
+}
+
+/// Ranks quotes by the shortest transit time, leaving out those whose
+/// transit time cannot be read (see [`Service::transit_days`])
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Fastest;
+
+impl<M> Strategy<M> for Fastest where M: Amount {
+    fn score(&self, quote: &Quote<'_, M>, _: &[Quote<'_, M>]) -> Option<Score> {
+        let days = quote.service.transit_days()?;
+        Some(Score {
+            value: days.into(),
+            reason: format!("{} in transit ({:?})", plural(days, "day"), quote.service.transit_time),
+        })
+    }
+}
+
+/// Ranks quotes by the soonest pickup date, leaving out those without one
+#[derive(Debug, Copy, Clone, Default)]
+pub struct EarliestPickup;
+
+impl<M> Strategy<M> for EarliestPickup where M: Amount {
+    fn score(&self, quote: &Quote<'_, M>, _: &[Quote<'_, M>]) -> Option<Score> {
+        let date = quote.earliest_pickup()?;
+        Some(Score {
+            value: date.to_julian_day().into(),
+            reason: format!("picked up from {date}"),
+        })
+    }
+}
+
+/// Ranks quotes by a weighted blend of price, transit time and pickup date
+///
+/// Each is scaled between the best (0) and worst (1) of the quotes being
+/// ranked, so the weights say how much each matters relative to the others.
+/// Quotes whose transit time or pickup date cannot be read count as the
+/// worst for it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BestValue {
+    pub price: f64,
+    pub transit: f64,
+    pub pickup: f64,
+}
+
+/// Mostly on price, then transit time, then pickup date
+impl Default for BestValue {
+    fn default() -> Self {
+        BestValue {
+            price: 0.6,
+            transit: 0.3,
+            pickup: 0.1,
+        }
+    }
+}
+
+impl<M> Strategy<M> for BestValue where M: Amount {
+    fn score(&self, quote: &Quote<'_, M>, quotes: &[Quote<'_, M>]) -> Option<Score> {
+        let price = scale(quote, quotes, |q| Some(q.total()));
+        let transit = scale(quote, quotes, |q| q.service.transit_days().map(f64::from));
+        let pickup = scale(quote, quotes, |q| q.earliest_pickup().map(|d| d.to_julian_day().into()));
+
+        let weights = self.price + self.transit + self.pickup;
+        let value = if weights > 0.0 {
+            (self.price * price + self.transit * transit + self.pickup * pickup) / weights
+        } else {
+            0.0
+        };
+
+        Some(Score {
+            value,
+            reason: format!("{value:.2} from price {price:.2}, transit {transit:.2} and pickup {pickup:.2} \
+                (0 is best, 1 is worst)"),
+        })
+    }
+}
+
+// Where a quote falls between the best and worst of all the quotes
+fn scale<M, F>(quote: &Quote<'_, M>, quotes: &[Quote<'_, M>], measure: F) -> f64
+where M: Amount, F: Fn(&Quote<'_, M>) -> Option<f64> {
+    let Some(value) = measure(quote) else {
+        return 1.0;
+    };
+    let (least, most) = quotes
+        .iter()
+        .filter_map(&measure)
+        .fold((value, value), |(least, most), v| (least.min(v), most.max(v)));
+
+    if most > least { (value - least) / (most - least) } else { 0.0 }
+}
+
+fn plural(n: u32, unit: &str) -> String {
+    if n == 1 { format!("1 {unit}") } else { format!("{n} {unit}s") }
+}
+
+/// Orders quotes by a [`Strategy`], best first
+///
+/// Ties are broken by the lower total price, then by courier, so rankings
+/// are always in the same order. Quotes scored as NaN are left out.
+pub struct Ranker<'s, M> where M: Amount {
+    strategy: Box<dyn Strategy<M> + 's>,
+    allow: Option<HashSet<Courier>>,
+    deny: HashSet<Courier>,
+}
+
+impl<'s, M> Ranker<'s, M> where M: Amount {
+    pub fn new(strategy: impl Strategy<M> + 's) -> Self {
+        Ranker {
+            strategy: Box::new(strategy),
+            allow: None,
+            deny: HashSet::new(),
+        }
+    }
+
+    /// Only ranks quotes from these couriers, which may be given more
+    /// than once to allow more of them
+    pub fn allow(mut self, couriers: impl IntoIterator<Item = Courier>) -> Self {
+        self.allow.get_or_insert_with(HashSet::new).extend(couriers);
+        self
+    }
+
+    /// Never ranks quotes from these couriers, even if they are allowed
+    pub fn deny(mut self, couriers: impl IntoIterator<Item = Courier>) -> Self {
+        self.deny.extend(couriers);
+        self
+    }
+
+    fn permits(&self, courier: &Courier) -> bool {
+        !self.deny.contains(courier) && self.allow.as_ref().is_none_or(|allow| allow.contains(courier))
+    }
+
+    /// The permitted quotes which the strategy scores, best first
+    pub fn rank<'a>(&self, quotes: &'a Quotes<M>) -> Vec<Ranked<'a, M>> {
+        let candidates: Vec<Quote<'a, M>> = quotes
+            .iter()
+            .filter(|(courier, _)| self.permits(courier))
+            .map(|(courier, service)| Quote { courier, service })
+            .collect();
+
+        let mut ranked: Vec<Ranked<'a, M>> = candidates
+            .iter()
+            .filter_map(|quote| {
+                let Score { value, reason } = self.strategy.score(quote, &candidates).filter(|s| !s.value.is_nan())?;
+                Some(Ranked { courier: quote.courier, service: quote.service, score: value, reason })
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.score.total_cmp(&b.score)
+            .then(a.service.total.as_f64().total_cmp(&b.service.total.as_f64()))
+            .then(a.courier.cmp(b.courier)));
+        ranked
+    }
+
+    /// The best of the permitted quotes
+    pub fn best<'a>(&self, quotes: &'a Quotes<M>) -> Option<Ranked<'a, M>> {
+        self.rank(quotes).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotes() -> Quotes<f64> {
+        serde_json::from_value(serde_json::json!({
+            "couriers_please_domestic_priority": service(23.54, "1-3 days", &["2024-03-05", "2024-03-06"]),
+            "toll": service(31.20, "2-4 Business Days", &["2024-03-04"]),
+            "toll_priority_overnight": service(58.90, "Overnight", &["2024-03-05"]),
+            "fastway": service(19.99, "Depends", &[]),
+        })).unwrap()
+    }
+
+    fn service(total: f64, transit_time: &str, pickup_dates: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "total": total, "price_insurance_ex": total, "fee": 0.0, "insured_amount": 0.0,
+            "service": "road", "transit_time": transit_time, "pickup_dates": pickup_dates, "pickup_time": {},
+        })
+    }
+
+    fn couriers<'a>(ranked: &[Ranked<'a, f64>]) -> Vec<&'a str> {
+        ranked.iter().map(|r| r.courier.as_str()).collect()
+    }
+
+    #[test]
+    fn should_rank_by_each_strategy() {
+        let quotes = quotes();
+
+        let cheapest = Ranker::new(Cheapest).rank(&quotes);
+        assert_eq!(couriers(&cheapest), ["fastway", "couriers_please_domestic_priority", "toll", "toll_priority_overnight"]);
+        assert_eq!(cheapest[0].reason, "$19.99 in total");
+
+        let fastest = Ranker::new(Fastest).rank(&quotes);
+        assert_eq!(couriers(&fastest), ["toll_priority_overnight", "couriers_please_domestic_priority", "toll"]);
+        assert_eq!(fastest[0].reason, r#"1 day in transit ("Overnight")"#);
+
+        let pickup = Ranker::new(EarliestPickup).rank(&quotes);
+        assert_eq!(couriers(&pickup), ["toll", "couriers_please_domestic_priority", "toll_priority_overnight"]);
+        assert_eq!(pickup[0].reason, "picked up from 2024-03-04");
+    }
+
+    #[test]
+    fn should_weigh_best_value_and_filter_couriers() {
+        let quotes = quotes();
+
+        let ranked = Ranker::new(BestValue::default()).deny([Courier::Fastway]).rank(&quotes);
+        assert_eq!(couriers(&ranked), ["couriers_please_domestic_priority", "toll", "toll_priority_overnight"]);
+        assert!(ranked.windows(2).all(|w| w[0].score <= w[1].score));
+
+        let speed = BestValue { price: 0.0, transit: 1.0, pickup: 0.0 };
+        let best = Ranker::new(speed).allow([Courier::Toll]).allow([Courier::TollPriorityOvernight]).best(&quotes).unwrap();
+        assert_eq!(best.courier, &Courier::TollPriorityOvernight);
+        assert_eq!(best.score, 0.0);
+
+        let none = Ranker::new(Cheapest).allow([Courier::Toll]).deny([Courier::Toll]).rank(&quotes);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn should_leave_out_unscorable_quotes() {
+        struct Broken;
+
+        impl Strategy<f64> for Broken {
+            fn score(&self, quote: &Quote<'_, f64>, _: &[Quote<'_, f64>]) -> Option<Score> {
+                let value = if quote.courier == &Courier::Toll { f64::NAN } else { -quote.total() };
+                Some(Score { value, reason: String::new() })
+            }
+        }
+
+        let quotes = quotes();
+        let ranked = Ranker::new(Broken).rank(&quotes);
+        assert_eq!(couriers(&ranked), ["toll_priority_overnight", "couriers_please_domestic_priority", "fastway"]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::Error;
+use crate::transport::Method;
+
+/// How failed requests are retried
+/// 
+/// A request is retried when the server is rate limiting (429) or failing
+/// (5xx), or when no response arrived at all. Waits grow exponentially from
+/// `initial_backoff`, with random jitter so many clients do not retry in
+/// lockstep. A `Retry-After` header, when the server sends one, is honoured
+/// instead; if it asks for longer than `max_backoff`, the error is returned
+/// straight away.
+/// 
+/// Only reads are retried by default. Updates and deletes (e.g. cancelling
+/// a booking or deleting an order) are only retried if `retry_writes` is
+/// set: when the response to the first attempt is lost, the retry finds the
+/// work already done and fails with a 404 or the like. Getting quotes
+/// creates a new booking each time, so it is only retried if
+/// `retry_quotes` is set. Confirming bookings and creating orders are never
+/// retried.
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// use std::time::Duration;
+/// use transdirect::retry::RetryPolicy;
+/// use transdirect::client::ClientBuilder;
+/// 
+/// let c = ClientBuilder::new()
+///     .retry(RetryPolicy {
+///         max_attempts: 5,
+///         initial_backoff: Duration::from_secs(1),
+///         retry_quotes: true,
+///         ..RetryPolicy::default()
+///     })
+///     .build()
+///     .expect("Valid base URL");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32, // Including the first attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: bool,
+    pub retry_writes: bool, // PUT and DELETE
+    pub retry_quotes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retry_writes: false,
+            retry_quotes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a call with `method` may be retried at all, given whether
+    /// it is idempotent
+    pub fn permits(&self, method: Method, idempotent: bool) -> bool {
+        idempotent && (self.retry_writes || !matches!(method, Method::Put | Method::Delete))
+    }
+
+    /// The wait before retrying after the `attempt`th failure (from 1),
+    /// ignoring jitter and `Retry-After`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+
+        Duration::try_from_secs_f64(backoff)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// The wait before retrying after the `attempt`th failure with `err`, or
+    /// `None` if the request should not be retried
+    pub fn delay(&self, attempt: u32, idempotent: bool, err: &Error) -> Option<Duration> {
+        if !idempotent || attempt >= self.max_attempts || !err.is_retryable() {
+            return None;
+        }
+
+        match err.retry_after() {
+            Some(wait) if wait > self.max_backoff => None,
+            Some(wait) => Some(wait),
+            None if self.jitter => Some(self.backoff(attempt).mul_f64(fastrand::f64())),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// Runs `request` until it succeeds or should no longer be retried
+    pub fn run<R>(&self, idempotent: bool, mut request: impl FnMut() -> Result<R, Error>) -> Result<R, Error> {
+        let mut attempt = 1;
+
+        loop {
+            match request() {
+                Err(err) => match self.delay(attempt, idempotent, &err) {
+                    Some(wait) => std::thread::sleep(wait),
+                    None => return Err(err),
+                },
+                ok => return ok,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Asynchronous version of [`RetryPolicy::run`], waiting on the tokio
+    /// timer between attempts
+    #[cfg(feature = "async")]
+    pub async fn run_async<R, F, Fut>(&self, idempotent: bool, mut request: F) -> Result<R, Error>
+    where F: FnMut() -> Fut, Fut: std::future::Future<Output = Result<R, Error>> {
+        let mut attempt = 1;
+
+        loop {
+            match request().await {
+                Err(err) => match self.delay(attempt, idempotent, &err) {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => return Err(err),
+                },
+                ok => return ok,
+            }
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16, retry_after: Option<Duration>) -> Error {
+        Error::HTTPError { status, api_error: None, body: String::new(), retry_after }
+    }
+
+    #[test]
+    fn should_back_off_exponentially() {
+        let policy = RetryPolicy { max_backoff: Duration::from_secs(3), ..RetryPolicy::default() };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn should_only_retry_transient_failures() {
+        let policy = RetryPolicy { jitter: false, ..RetryPolicy::default() };
+
+        assert_eq!(policy.delay(1, true, &status(503, None)), Some(Duration::from_millis(500)));
+        assert_eq!(policy.delay(1, true, &status(429, Some(Duration::from_secs(7)))), Some(Duration::from_secs(7)));
+        assert_eq!(policy.delay(1, true, &status(429, Some(Duration::from_secs(60)))), None);
+        assert_eq!(policy.delay(1, true, &status(422, None)), None);
+        assert_eq!(policy.delay(1, false, &status(503, None)), None);
+        assert_eq!(policy.delay(3, true, &status(503, None)), None);
+    }
+
+    #[test]
+    fn should_only_retry_writes_when_asked() {
+        let policy = RetryPolicy::default();
+        assert!(policy.permits(Method::Get, true));
+        assert!(!policy.permits(Method::Delete, true));
+        assert!(!policy.permits(Method::Put, true));
+        assert!(!policy.permits(Method::Post, false));
+
+        let policy = RetryPolicy { retry_writes: true, ..RetryPolicy::default() };
+        assert!(policy.permits(Method::Delete, true));
+        assert!(policy.permits(Method::Put, true));
+    }
+
+    #[test]
+    fn should_stop_after_max_attempts() {
+        let policy = RetryPolicy { initial_backoff: Duration::ZERO, ..RetryPolicy::default() };
+        let mut attempts = 0;
+
+        let result: Result<(), Error> = policy.run(true, || {
+            attempts += 1;
+            Err(status(502, None))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+}
@@ -0,0 +1,940 @@
+//! An in-process fake of the Transdirect API, for testing offline
+//!
+//! [`FakeTransdirect`] is a [`Transport`] which answers requests itself
+//! instead of sending them anywhere. It keeps bookings, orders and tracking
+//! events in memory and implements enough of the API to walk through the
+//! whole shipping flow: authenticating, quoting, updating, confirming and
+//! cancelling bookings, managing orders, tracking, downloading labels and
+//! searching locations.
+//!
+//! Canned data can be replaced through the `set_*` and `insert_*` methods,
+//! and any endpoint can be made to answer with a fixed response through
+//! [`FakeTransdirect::on`] and [`FakeTransdirect::on_once`], e.g. to test
+//! error handling. Every request received is recorded.
+//!
+//! Only available with the `testing` feature enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use transdirect::{BookingRequest, BookingResponse, Courier, Product};
+//! use transdirect::testing::{self, FakeTransdirect};
+//! use transdirect::transport::Method;
+//!
+//! let fake = FakeTransdirect::new();
+//! let c = fake.client();
+//!
+//! let request = BookingRequest { items: vec![Product::new()], ..BookingRequest::new() };
+//! let quote: BookingResponse = c.quotes(&request).expect("Quoted");
+//! assert!(quote.quotes.for_courier(&Courier::Toll).is_some());
+//!
+//! let path = format!("bookings/v4/{}", quote.id);
+//! fake.on_once(Method::Get, &path, testing::json(404, &serde_json::json!({ "message": "Gone" })));
+//! let err = c.booking::<u32, f64, f64>(quote.id).expect_err("Programmed to fail");
+//! assert_eq!(err.status(), Some(404));
+//! assert!(c.booking::<u32, f64, f64>(quote.id).is_ok());
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use url::Url;
+
+use crate::Error;
+use crate::client::{Client, ClientBuilder};
+use crate::transport::{Method, Request, Response, Transport};
+
+/// The base URL of clients made by [`FakeTransdirect::builder`]
+pub const BASE_URL: &str = "http://transdirect.test/api/";
+
+/// A response with a JSON body
+pub fn json<B>(status: u16, body: &B) -> Response
+where B: Serialize + ?Sized {
+    Response {
+        status,
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: serde_json::to_vec(body).expect("Body should be serializable as JSON"),
+    }
+}
+
+/// A response with no body
+pub fn empty(status: u16) -> Response {
+    Response { status, ..Response::default() }
+}
+
+/// An in-memory stand-in for the Transdirect API; see [`crate::testing`]
+///
+/// Clones share the same state, so a clone can be handed to a client while
+/// the original is used to inspect or program it.
+#[derive(Clone, Default)]
+pub struct FakeTransdirect {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    member: Value,
+    quotes: Value,
+    locations: Vec<Value>,
+    bookings: BTreeMap<u32, Value>,
+    orders: BTreeMap<u32, Value>,
+    tracking: HashMap<String, Value>, // Events by connote
+    next_booking_id: u32,
+    next_order_id: u32,
+    require_auth: bool,
+    routes: Vec<Route>,
+    requests: Vec<Request>,
+}
+
+// A programmed response, used `remaining` more times (or forever)
+struct Route {
+    method: Method,
+    path: String,
+    response: Response,
+    remaining: Option<usize>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            member: json!({
+                "id": 1001,
+                "user_id": 5001,
+                "company_name": "Fake Freight Pty Ltd",
+                "contact_name": "Sam Fake",
+                "email": "sam@fakefreight.test",
+                "phone": "02 9000 0000",
+                "abn": "12 345 678 901",
+                "postcode": "2000",
+                "active": true,
+                "default_sender": {
+                    "address": "1 Fake St", "email": "dispatch@fakefreight.test", "name": "Dispatch",
+                    "postcode": "2000", "state": "NSW", "suburb": "Sydney", "type": "business",
+                    "country": "AU", "company_name": "Fake Freight Pty Ltd",
+                },
+                "balance": 250.0,
+                "credit_limit": 1000.0,
+                "settings": { "insure_by_default": false, "tailgate_pickup": false, "notifications": {} },
+            }),
+            quotes: canned_quotes(),
+            locations: canned_locations(),
+            bookings: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            tracking: HashMap::new(),
+            next_booking_id: 1,
+            next_order_id: 1,
+            require_auth: false,
+            routes: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl FakeTransdirect {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A client builder pointed at this fake, for both blocking and (with
+    /// the `async` feature) async clients
+    pub fn builder<'a>(&self) -> ClientBuilder<'a> {
+        let builder = ClientBuilder::new()
+            .base_url(BASE_URL)
+            .transport(self.clone());
+
+        #[cfg(feature = "async")]
+        let builder = builder.async_transport(self.clone());
+
+        builder
+    }
+
+    pub fn client<'a>(&self) -> Client<'a> {
+        self.builder()
+            .build()
+            .expect("Fake base URL should be valid")
+    }
+
+    /// Rejects requests without credentials with a 401, as the real API
+    /// does. Off by default, like the Apiary mock.
+    pub fn require_auth(&self, require_auth: bool) {
+        self.state().require_auth = require_auth;
+    }
+
+    /// Answers every request to `path` (relative to the API, without a
+    /// query string) with `response`, until overridden
+    pub fn on(&self, method: Method, path: &str, response: Response) {
+        self.route(method, path, response, None);
+    }
+
+    /// Answers the next request to `path` with `response`, then goes back
+    /// to the previous behaviour. Queued responses are used in order.
+    pub fn on_once(&self, method: Method, path: &str, response: Response) {
+        self.route(method, path, response, Some(1));
+    }
+
+    fn route(&self, method: Method, path: &str, response: Response, remaining: Option<usize>) {
+        let path = path.trim_matches('/').to_string();
+        let mut state = self.state();
+
+        // Later permanent routes replace earlier ones; one-off routes queue
+        if remaining.is_none() {
+            state.routes.retain(|r| !(r.method == method && r.path == path && r.remaining.is_none()));
+        }
+        state.routes.push(Route { method, path, response, remaining });
+    }
+
+    /// Every request received so far, oldest first
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+
+    /// Replaces the member returned by `GET member`
+    pub fn set_member<B>(&self, member: &B)
+    where B: Serialize + ?Sized {
+        self.state().member = to_value(member);
+    }
+
+    /// Replaces the quotes given to every new booking, keyed by courier
+    pub fn set_quotes<B>(&self, quotes: &B)
+    where B: Serialize + ?Sized {
+        self.state().quotes = to_value(quotes);
+    }
+
+    /// Replaces the locations searched by `GET locations`
+    pub fn set_locations<B>(&self, locations: &[B])
+    where B: Serialize {
+        self.state().locations = locations.iter().map(to_value).collect();
+    }
+
+    /// Stores a booking as JSON, returning its id. An id is assigned if the
+    /// booking has none.
+    pub fn insert_booking(&self, booking: Value) -> u32 {
+        let mut state = self.state();
+        let id = state.assign_booking_id(&booking);
+        state.bookings.insert(id, with_id(booking, id));
+        id
+    }
+
+    /// Stores an order, returning its id. An id is assigned if the order has
+    /// none.
+    pub fn insert_order<B>(&self, order: &B) -> u32
+    where B: Serialize + ?Sized {
+        let mut state = self.state();
+        let order = to_value(order);
+        let id = state.assign_order_id(&order);
+        state.orders.insert(id, with_id(order, id));
+        id
+    }
+
+    /// Sets the tracking events for a connote, as a JSON array
+    pub fn set_tracking(&self, connote: &str, events: Value) {
+        self.state().tracking.insert(connote.to_string(), events);
+    }
+
+    /// The stored booking, as JSON
+    pub fn booking(&self, id: u32) -> Option<Value> {
+        self.state().bookings.get(&id).cloned()
+    }
+
+    /// The stored order, as JSON
+    pub fn order(&self, id: u32) -> Option<Value> {
+        self.state().orders.get(&id).cloned()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Transport for FakeTransdirect {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let url = Url::parse(&request.url).map_err(|e| Error::Transport(Box::new(e)))?;
+        let base = Url::parse(BASE_URL).expect("Fake base URL should be valid");
+        let path = url.path()
+            .strip_prefix(base.path())
+            .unwrap_or(url.path())
+            .trim_matches('/')
+            .to_string();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let body = request.body
+            .as_deref()
+            .and_then(|b| serde_json::from_slice(b).ok())
+            .unwrap_or(Value::Null);
+
+        let mut state = self.state();
+        state.requests.push(request.clone());
+
+        if let Some(response) = state.programmed(request.method, &path) {
+            return Ok(response);
+        }
+        if state.require_auth && request.header("Authorization").is_none() && request.header("Api-key").is_none() {
+            return Ok(json(401, &json!({ "message": "Unauthenticated." })));
+        }
+
+        let segments: Vec<&str> = path.split('/').collect();
+        Ok(state.handle(request.method, &segments, &query, body))
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::transport::AsyncTransport for FakeTransdirect {
+    fn send<'a>(&'a self, request: &'a Request) -> crate::transport::BoxFuture<'a, Result<Response, Error>> {
+        Box::pin(async move { Transport::send(self, request) })
+    }
+}
+
+impl State {
+    fn programmed(&mut self, method: Method, path: &str) -> Option<Response> {
+        let index = self.routes
+            .iter()
+            .position(|r| r.method == method && r.path == path && r.remaining.is_some())
+            .or_else(|| self.routes.iter().position(|r| r.method == method && r.path == path))?;
+        let route = &mut self.routes[index];
+        let response = route.response.clone();
+
+        if let Some(remaining) = &mut route.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.routes.remove(index);
+            }
+        }
+
+        Some(response)
+    }
+
+    fn handle(&mut self, method: Method, segments: &[&str], query: &HashMap<String, String>, body: Value) -> Response {
+        use Method::*;
+
+        match (method, segments) {
+            (Get, ["member"]) => json(200, &self.member),
+            (Put, ["member"]) => self.update_member(body),
+            (Get, ["locations"]) => self.search_locations(query),
+
+            (Post, ["bookings", "v4"]) => self.create_booking(body),
+            (Get, ["bookings", "v4"]) => self.list_bookings(query),
+            (Get, ["bookings", "v4", id]) => match self.booking(id) {
+                Some(booking) => json(200, booking),
+                None => not_found("Booking"),
+            },
+            (Put, ["bookings", "v4", id]) => self.update_booking(id, body),
+            (Delete, ["bookings", "v4", id]) => match self.booking_mut(id) {
+                Some(booking) => {
+                    booking["status"] = json!("cancelled");
+                    booking["updated_at"] = json!(now());
+                    empty(204)
+                },
+                None => not_found("Booking"),
+            },
+            (Post, ["bookings", "v4", id, "confirm"]) => self.confirm_booking(id, body),
+            (Get, ["bookings", "v4", id, "tracking"]) => match self.booking(id).map(|b| b["connote"].clone()) {
+                Some(Value::String(connote)) => self.tracking(&connote),
+                Some(_) => json(404, &json!({ "message": "Booking has not been confirmed." })),
+                None => not_found("Booking"),
+            },
+            (Get, ["bookings", "v4", id, kind @ ("label" | "consignment-note" | "manifest")]) => {
+                match self.booking(id).map(|b| b["connote"].is_string()) {
+                    Some(true) => Response {
+                        status: 200,
+                        headers: vec![("Content-Type".to_string(), "application/pdf".to_string())],
+                        body: format!("%PDF-1.4\n% Fake {kind} for booking {id}\n%%EOF\n").into_bytes(),
+                    },
+                    Some(false) => json(404, &json!({ "message": "Booking has not been confirmed." })),
+                    None => not_found("Booking"),
+                }
+            },
+            (Get, ["tracking", connote]) => self.tracking(connote),
+
+            (Post, ["orders"]) => {
+                let order = self.create_order(body);
+                json(201, &order)
+            },
+            (Post, ["orders", "bulk"]) => {
+                let orders: Vec<Value> = match body {
+                    Value::Array(orders) => orders.into_iter().map(|o| self.create_order(o)).collect(),
+                    _ => return invalid("orders", "A list of orders is required."),
+                };
+                json(201, &orders)
+            },
+            (Get, ["orders"]) => self.list_orders(query),
+            (Get, ["orders", id]) => match self.order(id) {
+                Some(order) => json(200, order),
+                None => not_found("Order"),
+            },
+            (Put, ["orders", id]) => self.update_order(id, body),
+            (Delete, ["orders", id]) => match id.parse().ok().and_then(|id: u32| self.orders.remove(&id)) {
+                Some(_) => empty(204),
+                None => not_found("Order"),
+            },
+
+            _ => json(404, &json!({ "message": "Not found." })),
+        }
+    }
+
+    fn assign_booking_id(&mut self, booking: &Value) -> u32 {
+        let id = id_of(booking).unwrap_or(self.next_booking_id);
+        self.next_booking_id = self.next_booking_id.max(id + 1);
+        id
+    }
+
+    fn assign_order_id(&mut self, order: &Value) -> u32 {
+        let id = id_of(order).unwrap_or(self.next_order_id);
+        self.next_order_id = self.next_order_id.max(id + 1);
+        id
+    }
+
+    fn booking(&self, id: &str) -> Option<&Value> {
+        self.bookings.get(&id.parse().ok()?)
+    }
+
+    fn booking_mut(&mut self, id: &str) -> Option<&mut Value> {
+        self.bookings.get_mut(&id.parse().ok()?)
+    }
+
+    fn order(&self, id: &str) -> Option<&Value> {
+        self.orders.get(&id.parse().ok()?)
+    }
+
+    fn create_booking(&mut self, request: Value) -> Response {
+        if request["items"].as_array().is_none_or(|items| items.is_empty()) {
+            return invalid("items", "At least one item is required.");
+        }
+
+        let id = self.assign_booking_id(&Value::Null);
+        let now = now();
+        let booking = json!({
+            "id": id,
+            "status": "new",
+            "booked_at": now,
+            "booked_by": "sender",
+            "created_at": now,
+            "updated_at": now,
+            "declared_value": or_default(&request["declared_value"], json!(0.0)),
+            "insured_value": 0.0,
+            "description": null,
+            "items": request["items"],
+            "label": "",
+            "notifications": { "email": true, "sms": false },
+            "quotes": self.quotes,
+            "sender": or_default(&request["sender"], blank_account()),
+            "receiver": or_default(&request["receiver"], blank_account()),
+            "pickup_window": [],
+            "connote": null,
+            "charged_weight": charged_weight(&request["items"]),
+            "scanned_weight": 0,
+            "special_instructions": "",
+            "tailgate_delivery": or_default(&request["tailgate_delivery"], json!(false)),
+        });
+
+        self.bookings.insert(id, booking.clone());
+        json(201, &booking)
+    }
+
+    fn update_booking(&mut self, id: &str, request: Value) -> Response {
+        let quotes = self.quotes.clone();
+        let Some(booking) = self.booking_mut(id) else {
+            return not_found("Booking");
+        };
+        if booking["status"] != "new" {
+            return json(422, &json!({ "message": "Only new bookings can be updated." }));
+        }
+
+        for field in ["declared_value", "items", "sender", "receiver", "tailgate_delivery"] {
+            if !request[field].is_null() {
+                booking[field] = request[field].clone();
+            }
+        }
+        booking["charged_weight"] = charged_weight(&booking["items"]);
+        booking["quotes"] = quotes;
+        booking["updated_at"] = json!(now());
+
+        json(200, booking)
+    }
+
+    fn confirm_booking(&mut self, id: &str, request: Value) -> Response {
+        let Some(booking) = self.booking_mut(id) else {
+            return not_found("Booking");
+        };
+        if booking["status"] != "new" {
+            return json(422, &json!({ "message": "Only new bookings can be confirmed." }));
+        }
+        let courier = request["courier"].as_str().unwrap_or_default();
+        if booking["quotes"].get(courier).is_none() {
+            return invalid("courier", "The selected courier was not quoted.");
+        }
+        let Some(pickup_date) = request["pickup-date"].as_str() else {
+            return invalid("pickup-date", "A pickup date is required.");
+        };
+
+        let booking_id = booking["id"].as_u64().unwrap_or_default();
+        let connote = format!("FAKE{booking_id:08}");
+        booking["status"] = json!("confirmed");
+        booking["connote"] = json!(connote);
+        booking["label"] = json!(format!("{BASE_URL}bookings/v4/{booking_id}/label"));
+        booking["pickup_window"] = json!([pickup_date]);
+        booking["booked_at"] = json!(now());
+        booking["updated_at"] = json!(now());
+
+        let booked_at = booking["booked_at"].clone();
+        self.tracking.entry(connote).or_insert_with(|| json!([{
+            "timestamp": booked_at,
+            "location": null,
+            "status": "booked",
+            "description": "Booking confirmed with courier",
+        }]));
+
+        empty(200)
+    }
+
+    fn tracking(&self, connote: &str) -> Response {
+        match self.tracking.get(connote) {
+            Some(events) => json(200, events),
+            None => not_found("Consignment"),
+        }
+    }
+
+    fn create_order(&mut self, order: Value) -> Value {
+        let id = self.assign_order_id(&Value::Null);
+        let now = now();
+        let mut order = with_id(order, id);
+
+        order["transdirect_order_id"] = json!(id);
+        if order["status"].is_null() {
+            order["status"] = json!("pending");
+        }
+        order["created_at"] = json!(now);
+        order["updated_at"] = json!(now);
+
+        self.orders.insert(id, order.clone());
+        order
+    }
+
+    // Filters on the booking time, then sorts and pages
+    fn list_bookings(&self, query: &HashMap<String, String>) -> Response {
+        let date = |name: &str| query.get(name).map(|d| time::OffsetDateTime::parse(d, &Iso8601::DEFAULT));
+        let (since, until) = match (date("since").transpose(), date("until").transpose()) {
+            (Ok(since), Ok(until)) => (since, until),
+            _ => return invalid("since", "Dates must be ISO-8601."),
+        };
+        let booked_at = |b: &Value| b["booked_at"].as_str().and_then(|d| time::OffsetDateTime::parse(d, &Iso8601::DEFAULT).ok());
+
+        let mut bookings: Vec<&Value> = self.bookings
+            .values()
+            .filter(|b| query.get("status").is_none_or(|s| b["status"] == json!(s)))
+            .filter(|b| since.is_none_or(|since| booked_at(b).is_some_and(|at| at >= since)))
+            .filter(|b| until.is_none_or(|until| booked_at(b).is_some_and(|at| at < until)))
+            .collect();
+
+        if let Some(sort) = query.get("sort") {
+            let field = if sort == "booking_time" { "booked_at" } else { sort.as_str() };
+            bookings.sort_by(|a, b| a[field].to_string().cmp(&b[field].to_string()));
+            if query.get("direction").is_some_and(|d| d == "desc") {
+                bookings.reverse();
+            }
+        }
+
+        let page: usize = query.get("page").and_then(|p| p.parse().ok()).unwrap_or(1).max(1);
+        let per_page = query.get("per_page").and_then(|p| p.parse().ok()).unwrap_or(bookings.len().max(1));
+        let bookings: Vec<&Value> = bookings.into_iter().skip((page - 1) * per_page).take(per_page).collect();
+
+        json(200, &bookings)
+    }
+
+    // Balances and ids are kept, as the API does not let members change them
+    fn update_member(&mut self, body: Value) -> Response {
+        let Value::Object(fields) = body else {
+            return invalid("member", "A member profile is required.");
+        };
+
+        for (field, value) in fields {
+            if !["id", "user_id", "balance", "credit_limit"].contains(&field.as_str()) {
+                self.member[field] = value;
+            }
+        }
+        json(200, &self.member)
+    }
+
+    // Matches postcodes and suburbs by prefix, ignoring case
+    fn search_locations(&self, query: &HashMap<String, String>) -> Response {
+        let q = query.get("q").map(|q| q.trim().to_lowercase()).unwrap_or_default();
+        if q.is_empty() {
+            return invalid("q", "A postcode or suburb is required.");
+        }
+
+        let matches = |field: &Value| field.as_str().is_some_and(|f| f.to_lowercase().starts_with(&q));
+        let locations: Vec<&Value> = self.locations
+            .iter()
+            .filter(|l| matches(&l["postcode"]) || matches(&l["suburb"]))
+            .collect();
+
+        json(200, &json!({ "locations": locations }))
+    }
+
+    fn list_orders(&self, query: &HashMap<String, String>) -> Response {
+        let orders: Vec<&Value> = self.orders
+            .values()
+            .filter(|o| query.get("status").is_none_or(|status| o["status"] == status.as_str()))
+            .collect();
+
+        let per_page = query.get("per_page").and_then(|p| p.parse().ok()).unwrap_or(orders.len().max(1));
+        let page = query.get("page").and_then(|p| p.parse().ok()).unwrap_or(1usize).max(1);
+        let orders: Vec<&Value> = orders.into_iter().skip((page - 1) * per_page).take(per_page).collect();
+
+        json(200, &orders)
+    }
+
+    fn update_order(&mut self, id: &str, order: Value) -> Response {
+        let Some(existing) = id.parse().ok().and_then(|id: u32| self.orders.get_mut(&id)) else {
+            return not_found("Order");
+        };
+        let Value::Object(fields) = order else {
+            return invalid("order", "An order is required.");
+        };
+
+        for (field, value) in fields {
+            if !matches!(field.as_str(), "id" | "transdirect_order_id" | "created_at") {
+                existing[field] = value;
+            }
+        }
+        existing["updated_at"] = json!(now());
+
+        json(200, existing)
+    }
+}
+
+fn canned_quotes() -> Value {
+    let pickup_dates: Vec<String> = (1..=3)
+        .map(|days| (time::OffsetDateTime::now_utc() + time::Duration::days(days)).date().to_string())
+        .collect();
+    let quote = |total: f64, service: &str, transit_time: &str| json!({
+        "total": total,
+        "price_insurance_ex": (total / 1.1 * 100.0).round() / 100.0,
+        "fee": ((total - total / 1.1) * 100.0).round() / 100.0,
+        "insured_amount": 0.0,
+        "service": service,
+        "transit_time": transit_time,
+        "pickup_dates": pickup_dates,
+        "pickup_time": { "from": "09:00:00", "to": "17:00:00" },
+    });
+
+    json!({
+        "couriers_please_domestic_priority": quote(23.54, "road", "1-3 days"),
+        "toll": quote(31.20, "road", "2-4 Business Days"),
+        "toll_priority_overnight": quote(58.90, "air", "Overnight"),
+    })
+}
+
+fn canned_locations() -> Vec<Value> {
+    [
+        ("Sydney", "2000", "NSW"),
+        ("Mosman", "2088", "NSW"),
+        ("Canberra", "2600", "ACT"),
+        ("Melbourne", "3000", "VIC"),
+        ("Brisbane City", "4000", "QLD"),
+        ("Adelaide", "5000", "SA"),
+        ("Perth", "6000", "WA"),
+        ("East Perth", "6004", "WA"),
+        ("Hobart", "7000", "TAS"),
+        ("Darwin City", "0800", "NT"),
+    ]
+        .into_iter()
+        .map(|(suburb, postcode, state)| json!({ "suburb": suburb, "postcode": postcode, "state": state, "country": "AU" }))
+        .collect()
+}
+
+fn blank_account() -> Value {
+    to_value(&crate::account::Account::default())
+}
+
+// The total weight, rounded up to whole kilograms as Transdirect charges it
+fn charged_weight(items: &Value) -> Value {
+    let weight: f64 = items
+        .as_array()
+        .map(|items| items
+            .iter()
+            .map(|i| i["weight"].as_f64().unwrap_or_default() * i["quantity"].as_f64().unwrap_or(1.0))
+            .sum())
+        .unwrap_or_default();
+
+    json!(weight.ceil() as u64)
+}
+
+fn id_of(value: &Value) -> Option<u32> {
+    value["id"].as_u64().and_then(|id| id.try_into().ok())
+}
+
+fn with_id(value: Value, id: u32) -> Value {
+    let mut fields = match value {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    fields.insert("id".to_string(), json!(id));
+    Value::Object(fields)
+}
+
+fn or_default(value: &Value, default: Value) -> Value {
+    if value.is_null() { default } else { value.clone() }
+}
+
+fn to_value<B>(value: &B) -> Value
+where B: Serialize + ?Sized {
+    serde_json::to_value(value).expect("Value should be serializable as JSON")
+}
+
+fn now() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .expect("The current time should be formattable")
+}
+
+fn not_found(what: &str) -> Response {
+    json(404, &json!({ "message": format!("{what} not found.") }))
+}
+
+fn invalid(field: &str, message: &str) -> Response {
+    json(422, &json!({
+        "message": "The given data was invalid.",
+        "errors": { field: [message] },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Account, AustralianState, BookingQuery, BookingRequest, BookingResponse, BookingStatus, Courier, Order, OrderQuery, OrderStatus, Product};
+    use crate::booking::{BookingSort, SortDirection};
+    use crate::retry::RetryPolicy;
+    use crate::tracking::TrackingStatus;
+
+    fn request() -> BookingRequest<'static> {
+        let item = Product { weight: 2.5, quantity: 2, ..Product::new() };
+        BookingRequest { declared_value: 40.0, items: vec![item], ..BookingRequest::new() }
+    }
+
+    #[test]
+    fn should_walk_through_shipping_flow() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+
+        let quote: BookingResponse = c.quotes(&request()).expect("Quoted");
+        assert_eq!(quote.charged_weight, 5);
+        let (courier, service) = quote.quotes.cheapest().expect("Some quotes");
+        let pickup = time::Date::parse(&service.pickup_dates[0], &time::format_description::well_known::Iso8601::DATE)
+            .expect("Valid pickup date");
+
+        let booking: BookingResponse = c.confirm_booking(quote.id, courier, pickup).expect("Confirmed");
+        assert!(booking.is_confirmed());
+        let connote = booking.connote.expect("Connote assigned");
+
+        let events = c.tracking(connote.as_str()).expect("Tracked");
+        assert_eq!(events[0].status(), TrackingStatus::AwaitingPickup);
+        assert!(c.label(quote.id).expect("Label").is_pdf());
+
+        let cancelled: BookingResponse = c.cancel_booking(quote.id).expect("Cancelled");
+        assert!(!cancelled.is_confirmed());
+    }
+
+    #[test]
+    fn should_reject_unquoted_courier() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        let quote: BookingResponse = c.quotes(&request()).expect("Quoted");
+
+        let date = time::Date::from_calendar_date(2030, time::Month::January, 2).unwrap();
+        let err = c.confirm_booking::<u32, f64, f64>(quote.id, &Courier::Allied, date).expect_err("Not quoted");
+
+        assert!(err.is_validation());
+        assert!(!err.api_error().unwrap().field("courier").is_empty());
+    }
+
+    #[test]
+    fn should_manage_orders() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        let order = Order { order_id: "WEB-1".to_string(), ..Order::new() };
+
+        let created = c.create_orders(&[order.clone(), order]).expect("Created");
+        let mut first = created[0].clone();
+        first.status = OrderStatus::Cancelled;
+        c.update_order(first.id.unwrap(), &first).expect("Updated");
+
+        let query = OrderQuery { status: Some(OrderStatus::Pending), ..OrderQuery::default() };
+        let pending: Vec<Order> = c.orders(&query).expect("Listed");
+        assert_eq!(pending.len(), 1);
+
+        c.delete_order(pending[0].id.unwrap()).expect("Deleted");
+        assert!(fake.order(pending[0].id.unwrap()).is_none());
+    }
+
+    #[test]
+    fn should_keep_and_update_member() {
+        let mut c = FakeTransdirect::new().client();
+        c.auth(crate::AuthenticateWith::APIKey("key")).expect("Authenticated");
+
+        let mut member = c.member().expect("Kept after authenticating").clone();
+        assert_eq!(member.default_sender.as_ref().map(|s| s.suburb.as_str()), Some("Sydney"));
+
+        member.phone = "02 9999 9999".to_string();
+        member.balance = 1_000_000.0;
+        let updated = c.update_member(&member).expect("Updated");
+        assert_eq!(updated.phone, "02 9999 9999");
+        assert_eq!(updated.balance, 250.0);
+        assert_eq!(c.member(), Some(&updated));
+    }
+
+    #[test]
+    fn should_walk_booking_pages() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        for _ in 0..5 {
+            let _: BookingResponse = c.quotes(&request()).expect("Quoted");
+        }
+        c.cancel_booking::<u32, f64, f64>(2).expect("Cancelled");
+
+        let query = BookingQuery::new()
+            .since(time::OffsetDateTime::now_utc() - time::Duration::hours(1))
+            .sort(BookingSort::CreatedAt, SortDirection::Descending)
+            .per_page(2);
+        let requests = fake.requests().len();
+        let mut bookings = c.bookings::<u32, f64, f64>(&query);
+
+        assert_eq!(bookings.next().map(|b| b.unwrap().id), Some(5));
+        assert_eq!(fake.requests().len(), requests + 1); // Only the first page so far
+        assert_eq!(bookings.count(), 4);
+        assert_eq!(fake.requests().len(), requests + 3);
+
+        let cancelled: Vec<BookingResponse> = c.bookings(&query.status(BookingStatus::Cancelled))
+            .collect::<Result<_, _>>()
+            .expect("Listed");
+        assert_eq!(cancelled.len(), 1);
+    }
+
+    #[test]
+    fn should_stop_on_pages_with_nothing_new() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        let ids: Vec<u32> = (0..3).map(|_| c.quotes::<u32, f64, f64>(&request()).expect("Quoted").id).collect();
+        let booking = |id: u32| fake.booking(id).unwrap();
+        let query = BookingQuery::new().per_page(2);
+
+        // A server ignoring the page number
+        fake.on(Method::Get, "bookings/v4", json(200, &[booking(ids[0]), booking(ids[1])]));
+        let requests = fake.requests().len();
+        let listed: Vec<BookingResponse> = c.bookings(&query).collect::<Result<_, _>>().expect("Listed");
+        assert_eq!(listed.iter().map(|b| b.id).collect::<Vec<_>>(), ids[..2]);
+        assert_eq!(fake.requests().len(), requests + 2);
+
+        // A booking pushed onto the next page is only listed once
+        fake.on_once(Method::Get, "bookings/v4", json(200, &[booking(ids[0]), booking(ids[1])]));
+        fake.on_once(Method::Get, "bookings/v4", json(200, &[booking(ids[1]), booking(ids[2])]));
+        fake.on_once(Method::Get, "bookings/v4", json(200, &[booking(ids[2])]));
+        let listed: Vec<BookingResponse> = c.bookings(&query).collect::<Result<_, _>>().expect("Listed");
+        assert_eq!(listed.iter().map(|b| b.id).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn should_stop_on_the_last_page_reported() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        let ids: Vec<u32> = (0..2).map(|_| c.quotes::<u32, f64, f64>(&request()).expect("Quoted").id).collect();
+        let data = json!([fake.booking(ids[0]).unwrap(), fake.booking(ids[1]).unwrap()]);
+        let query = BookingQuery::new().per_page(2);
+
+        for page in [json!({ "data": data, "meta": { "current_page": 1, "last_page": 1 } }),
+            json!({ "data": data, "current_page": 3, "last_page": 3, "per_page": 2 })] {
+            fake.on_once(Method::Get, "bookings/v4", json(200, &page));
+            let requests = fake.requests().len();
+
+            let listed: Vec<BookingResponse> = c.bookings(&query).collect::<Result<_, _>>().expect("Listed");
+            assert_eq!(listed.len(), 2);
+            assert_eq!(fake.requests().len(), requests + 1);
+        }
+    }
+
+    #[test]
+    fn should_follow_the_pages_reported_when_the_page_size_is_capped() {
+        let fake = FakeTransdirect::new();
+        let c = fake.client();
+        let ids: Vec<u32> = (0..3).map(|_| c.quotes::<u32, f64, f64>(&request()).expect("Quoted").id).collect();
+        let booking = |id: u32| fake.booking(id).unwrap();
+
+        // Asked for 5 a page, but only ever sends 2
+        fake.on_once(Method::Get, "bookings/v4", json(200, &json!({
+            "data": [booking(ids[0]), booking(ids[1])], "current_page": 1, "last_page": 2, "per_page": 2,
+        })));
+        fake.on_once(Method::Get, "bookings/v4", json(200, &json!({
+            "data": [booking(ids[2])], "current_page": 2, "last_page": 2, "per_page": 2,
+        })));
+        let requests = fake.requests().len();
+
+        let listed: Vec<BookingResponse> = c.bookings(&BookingQuery::new().per_page(5))
+            .collect::<Result<_, _>>()
+            .expect("Listed");
+        assert_eq!(listed.iter().map(|b| b.id).collect::<Vec<_>>(), ids);
+        assert_eq!(fake.requests().len(), requests + 2);
+    }
+
+    #[test]
+    fn should_search_locations() {
+        let c = FakeTransdirect::new().client();
+
+        let perth = c.locations("perth").expect("Searched");
+        assert_eq!(perth.len(), 1);
+        let receiver = Account::from(perth[0].clone());
+        assert_eq!((receiver.postcode.as_str(), &receiver.state), ("6000", &AustralianState::WesternAustralia));
+
+        assert_eq!(c.locations("20").expect("Searched").len(), 2);
+        assert!(c.locations(" ").unwrap_err().is_validation());
+    }
+
+    #[test]
+    fn should_retry_programmed_failures() {
+        let fake = FakeTransdirect::new();
+        let c = fake.builder()
+            .retry(RetryPolicy { initial_backoff: std::time::Duration::ZERO, ..RetryPolicy::default() })
+            .build()
+            .unwrap();
+        let quote: BookingResponse = c.quotes(&request()).expect("Quoted");
+
+        fake.on_once(Method::Get, &format!("bookings/v4/{}", quote.id), empty(503));
+        fake.on_once(Method::Get, &format!("bookings/v4/{}", quote.id), empty(429));
+
+        assert!(c.booking::<u32, f64, f64>(quote.id).is_ok());
+        assert_eq!(fake.requests().len(), 4);
+    }
+
+    #[test]
+    fn should_require_credentials_when_asked() {
+        let fake = FakeTransdirect::new();
+        fake.require_auth(true);
+        let mut c = fake.client();
+
+        assert!(c.booking::<u32, f64, f64>(1).unwrap_err().is_unauthorized());
+        c.auth(crate::AuthenticateWith::APIKey("key")).expect("Authenticated");
+        assert_eq!(fake.requests().last().unwrap().header("Api-key"), Some("key"));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn should_serve_async_clients() {
+        let fake = FakeTransdirect::new();
+        let c = fake.builder().build_async().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        let quote: BookingResponse = runtime.block_on(c.quotes(&request())).expect("Quoted");
+        assert!(fake.booking(quote.id).is_some());
+
+        let sizes = runtime.block_on(async {
+            for _ in 0..4 {
+                let _: BookingResponse = c.quotes(&request()).await.expect("Quoted");
+            }
+            let mut pages = c.bookings::<u32, f64, f64>(&BookingQuery::new().per_page(2));
+            let mut sizes = Vec::new();
+            while let Some(page) = pages.next_page().await {
+                sizes.push(page.expect("Listed").len());
+            }
+            sizes
+        });
+        assert_eq!(sizes, [2, 2, 1]);
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+
+use serde_derive::Deserialize;
+use url::Url;
+
+use crate::Error;
+use crate::client::RestPath;
+
+/// Identifies a shipment to track: either the Transdirect booking id or the
+/// courier's connote (consignment note or tracking number)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackingReference<'a> {
+    Booking(u32),
+    Connote(&'a str),
+}
+
+impl From<u32> for TrackingReference<'_> {
+    fn from(booking_id: u32) -> Self {
+        Self::Booking(booking_id)
+    }
+}
+
+impl<'a> From<&'a str> for TrackingReference<'a> {
+    fn from(connote: &'a str) -> Self {
+        Self::Connote(connote)
+    }
+}
+
+/// Normalised status of a shipment, suitable for showing to customers
+/// 
+/// Couriers each report their own status codes; [`TrackingStatus::from_code`]
+/// maps these onto the closest variant, falling back to `Unknown`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TrackingStatus {
+    AwaitingPickup,
+    PickedUp,
+    InTransit,
+    OutForDelivery,
+    DeliveryAttempted,
+    Delivered,
+    ReturnedToSender,
+    Exception,
+    #[default]
+    Unknown,
+}
+
+impl TrackingStatus {
+    /// Maps a courier status code (e.g. `"OUT_FOR_DELIVERY"` or
+    /// `"Picked up"`) onto a normalised status
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use transdirect::tracking::TrackingStatus;
+    /// 
+    /// assert_eq!(TrackingStatus::from_code("OUT_FOR_DELIVERY"), TrackingStatus::OutForDelivery);
+    /// assert_eq!(TrackingStatus::from_code("Delivery attempted - card left"), TrackingStatus::DeliveryAttempted);
+    /// ```
+    pub fn from_code(code: &str) -> Self {
+        let code = code.to_ascii_lowercase().replace(['_', '-'], " ");
+        let has = |needle: &str| code.contains(needle);
+
+        // Order matters: "undelivered" and "delivery attempted" both contain
+        // "deliver", and "return" takes precedence over anything in transit
+        if has("return") {
+            Self::ReturnedToSender
+        } else if has("attempt") || has("undeliver") || has("card left") {
+            Self::DeliveryAttempted
+        } else if has("exception") || has("damage") || has("lost") || has("held") || has("delay") {
+            Self::Exception
+        } else if has("delivered") {
+            Self::Delivered
+        } else if has("out for delivery") || has("onboard") || has("with driver") {
+            Self::OutForDelivery
+        } else if has("picked up") || has("pickup complete") || has("collected") {
+            Self::PickedUp
+        } else if has("transit") || has("depot") || has("sorted") || has("arrived") || has("departed") {
+            Self::InTransit
+        } else if has("booked") || has("manifest") || has("awaiting") || has("label") || has("created") {
+            Self::AwaitingPickup
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Whether no further events are expected for the shipment
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Delivered | Self::ReturnedToSender)
+    }
+}
+
+impl fmt::Display for TrackingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AwaitingPickup    => "Awaiting pickup",
+            Self::PickedUp          => "Picked up",
+            Self::InTransit         => "In transit",
+            Self::OutForDelivery    => "Out for delivery",
+            Self::DeliveryAttempted => "Delivery attempted",
+            Self::Delivered         => "Delivered",
+            Self::ReturnedToSender  => "Returned to sender",
+            Self::Exception         => "Exception",
+            Self::Unknown           => "Status unavailable",
+        })
+    }
+}
+
+/// A single scan or update reported by the courier
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TrackingEvent {
+    #[serde(with = "time::serde::iso8601", alias = "date", alias = "time")]
+    pub timestamp: time::OffsetDateTime,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(alias = "status", alias = "code")]
+    pub status_code: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl TrackingEvent {
+    /// The normalised status for this event's `status_code`
+    pub fn status(&self) -> TrackingStatus {
+        TrackingStatus::from_code(&self.status_code)
+    }
+}
+
+// The events are either returned bare or wrapped in an object
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum TrackingGroup {
+    Bare(Vec<TrackingEvent>),
+    Wrapped { events: Vec<TrackingEvent> },
+}
+
+impl TrackingGroup {
+    /// The events, oldest first
+    pub(crate) fn into_events(self) -> Vec<TrackingEvent> {
+        let mut events = match self {
+            Self::Bare(events) | Self::Wrapped { events } => events,
+        };
+        events.sort_by_key(|e| e.timestamp);
+        events
+    }
+}
+
+impl RestPath<TrackingReference<'_>> for TrackingGroup {
+    fn get_path(params: TrackingReference) -> Result<String, Error> {
+        match params {
+            TrackingReference::Booking(id) => Ok(format!("bookings/v4/{id}/tracking")),
+            TrackingReference::Connote(connote) => {
+                // Would otherwise be dropped, or take the path up a level
+                if ["", ".", ".."].contains(&connote.trim()) {
+                    return Err(Error::Unrecognised(connote.to_string()));
+                }
+
+                // Encoded as a single segment, so it cannot change the endpoint
+                let mut url = Url::parse("path:/").expect("Valid URL");
+                url.path_segments_mut()
+                    .expect("URL with a path")
+                    .pop_if_empty()
+                    .extend(["tracking", connote]);
+                Ok(url.path()[1..].to_string())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_normalise_status_codes() {
+        use TrackingStatus::*;
+
+        for (code, status) in [
+            ("BOOKED", AwaitingPickup),
+            ("Picked Up", PickedUp),
+            ("arrived_at_depot", InTransit),
+            ("onboard for delivery", OutForDelivery),
+            ("Undelivered - card left", DeliveryAttempted),
+            ("DELIVERED", Delivered),
+            ("returned-to-sender", ReturnedToSender),
+            ("Delayed: weather", Exception),
+            ("XYZ", Unknown),
+        ] {
+            assert_eq!(TrackingStatus::from_code(code), status, "{code}");
+        }
+        assert_eq!(TrackingStatus::from_code("Lost in transit").to_string(), "Exception");
+    }
+
+    #[test]
+    fn should_sort_wrapped_events() {
+        let group: TrackingGroup = serde_json::from_str(r#"{ "events": [
+            { "timestamp": "2023-03-05T09:12:00+10:00", "location": "Mosman NSW", "status": "delivered", "description": "Delivered" },
+            { "timestamp": "2023-03-03T14:00:00+08:00", "status": "picked_up", "description": "Picked up from sender" }
+        ]}"#).unwrap();
+        let events = group.into_events();
+
+        assert_eq!(events[0].status(), TrackingStatus::PickedUp);
+        assert_eq!(events[1].status(), TrackingStatus::Delivered);
+        assert_eq!(events[1].location.as_deref(), Some("Mosman NSW"));
+    }
+
+    #[test]
+    fn should_encode_connotes_as_one_segment() {
+        let path = |connote| TrackingGroup::get_path(TrackingReference::Connote(connote));
+
+        assert_eq!(path("CP123456").unwrap(), "tracking/CP123456");
+        assert_eq!(path("AB/12?page=2#top").unwrap(), "tracking/AB%2F12%3Fpage=2%23top");
+        assert!(path("..").is_err());
+        assert!(path("").is_err());
+    }
+}
@@ -0,0 +1,418 @@
+//! The HTTP layer underneath the clients
+//!
+//! Clients build each call into a plain [`Request`] (absolute URL, headers
+//! and JSON body) and hand it to a [`Transport`], which only has to return
+//! the status, headers and body of the [`Response`]. Everything
+//! Transdirect-specific (paths, credentials, retries, error payloads) stays
+//! in the client, so a transport can be backed by any HTTP library, or by
+//! none at all in tests.
+//!
+//! [`UreqTransport`] is used unless another transport is given to
+//! [`crate::client::ClientBuilder::transport`]. Proxies, TLS settings and
+//! connection pooling can be configured on the `ureq::Agent` it wraps.
+//! Async clients use [`HyperTransport`] in the same way, unless given
+//! another to [`crate::client::ClientBuilder::async_transport`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use transdirect::client::ClientBuilder;
+//! use transdirect::transport::UreqTransport;
+//!
+//! let agent = ureq::AgentBuilder::new()
+//!     .timeout(Duration::from_secs(10))
+//!     .build();
+//! let c = ClientBuilder::new()
+//!     .transport(UreqTransport::from(agent))
+//!     .build()
+//!     .expect("Valid base URL");
+//! ```
+use std::borrow::Cow;
+use std::io::Read;
+use std::time::Duration;
+
+use crate::Error;
+use crate::error::ApiError;
+
+/// HTTP methods used by the API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get    => "GET",
+            Self::Post   => "POST",
+            Self::Put    => "PUT",
+            Self::Delete => "DELETE",
+        }
+    }
+}
+
+/// A request ready to be sent, including credentials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String, // Absolute, including the query string
+    pub headers: Vec<(String, String)>, // Each name only once, in any case
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    /// The value of a header, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// A response as received, whatever its status
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// The value of a header, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("Content-Type")
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The body as text, with invalid UTF-8 replaced
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    // Reads a response received by `ureq`, whatever its status
+    pub(crate) fn from_ureq(response: ureq::Response) -> Result<Self, Error> {
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body).map_err(|e| Error::Transport(Box::new(e)))?;
+
+        Ok(Response { status, headers, body })
+    }
+
+    /// Turns an unsuccessful response into an [`Error::HTTPError`]
+    pub fn error_for_status(self) -> Result<Self, Error> {
+        if self.is_success() {
+            return Ok(self);
+        }
+
+        // Only the delay-seconds form; HTTP dates are rarely used
+        let retry_after = self
+            .header("Retry-After")
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_secs);
+        let body = self.text().into_owned();
+
+        Err(Error::HTTPError {
+            status: self.status,
+            api_error: ApiError::from_body(&body),
+            body,
+            retry_after,
+        })
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Sends requests on behalf of a blocking [`crate::client::Client`]
+///
+/// Implementations should return every response the server sends, including
+/// error statuses, and only fail when no response arrived. Such failures
+/// should be reported as [`Error::Transport`], and are retried by the
+/// client.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &Request) -> Result<Response, Error>;
+}
+
+impl<T> Transport for std::sync::Arc<T>
+where T: Transport + ?Sized {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        (**self).send(request)
+    }
+}
+
+impl<T> Transport for Box<T>
+where T: Transport + ?Sized {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        (**self).send(request)
+    }
+}
+
+/// The default transport, sending requests with `ureq`
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+impl UreqTransport {
+    pub fn new() -> Self {
+        Self { agent: ureq::Agent::new() }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ureq::Agent> for UreqTransport {
+    fn from(agent: ureq::Agent) -> Self {
+        Self { agent }
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let call = request.headers
+            .iter()
+            .fold(self.agent.request(request.method.as_str(), &request.url), |call, (name, value)| call.set(name, value));
+
+        let response = match &request.body {
+            Some(body) => call.send_bytes(body),
+            None => call.call(),
+        };
+        match response {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => Response::from_ureq(response),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// A boxed future, as returned by [`AsyncTransport::send`]
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Sends requests on behalf of a [`crate::async_client::AsyncClient`]
+///
+/// The asynchronous equivalent of [`Transport`], e.g. for `reqwest`:
+///
+/// ```ignore
+/// use transdirect::transport::{AsyncTransport, BoxFuture, Request, Response};
+///
+/// struct Reqwest(reqwest::Client);
+///
+/// impl AsyncTransport for Reqwest {
+///     fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, transdirect::Error>> {
+///         Box::pin(async move {
+///             let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes()).unwrap();
+///             let mut call = self.0.request(method, &request.url);
+///             for (name, value) in &request.headers {
+///                 call = call.header(name, value);
+///             }
+///             if let Some(body) = &request.body {
+///                 call = call.body(body.clone());
+///             }
+///             let response = call.send().await.map_err(|e| transdirect::Error::Transport(Box::new(e)))?;
+///
+///             let status = response.status().as_u16();
+///             let headers = response.headers()
+///                 .iter()
+///                 .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+///                 .collect();
+///             let body = response.bytes().await.map_err(|e| transdirect::Error::Transport(Box::new(e)))?.to_vec();
+///
+///             Ok(Response { status, headers, body })
+///         })
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncTransport: Send + Sync {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, Error>>;
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncTransport for std::sync::Arc<T>
+where T: AsyncTransport + ?Sized {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, Error>> {
+        (**self).send(request)
+    }
+}
+
+#[cfg(feature = "async")]
+type HttpsClient = hyper::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>;
+
+/// The default async transport, sending requests with `hyper` over
+/// `native-tls`
+///
+/// Connection pooling, HTTP/2 and the like can be configured on the
+/// `hyper::Client` it wraps.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct HyperTransport {
+    client: HttpsClient,
+}
+
+#[cfg(feature = "async")]
+impl HyperTransport {
+    pub fn new() -> Self {
+        Self { client: hyper::Client::builder().build(hyper_tls::HttpsConnector::new()) }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<HttpsClient> for HyperTransport {
+    fn from(client: HttpsClient) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for HyperTransport {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, Error>> {
+        Box::pin(async move {
+            let call = request.headers
+                .iter()
+                .fold(hyper::Request::builder().method(request.method.as_str()).uri(&request.url), |call, (name, value)| {
+                    call.header(name, value)
+                })
+                .body(request.body.clone().map_or_else(hyper::Body::empty, hyper::Body::from))
+                .map_err(|e| Error::Transport(Box::new(e)))?;
+
+            let response = self.client.request(call).await.map_err(|e| Error::Transport(Box::new(e)))?;
+
+            let status = response.status().as_u16();
+            let headers = response.headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                .collect();
+            let body = hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(|e| Error::Transport(Box::new(e)))?
+                .to_vec();
+
+            Ok(Response { status, headers, body })
+        })
+    }
+}
+
+/// Runs a blocking [`Transport`] on tokio's blocking thread pool, so it can
+/// be used by an async client
+///
+/// Each request holds a blocking thread for as long as it takes, so this is
+/// only worth using for a transport with no async equivalent.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Default)]
+pub struct Blocking<T>(std::sync::Arc<T>);
+
+#[cfg(feature = "async")]
+impl<T> Blocking<T>
+where T: Transport + 'static {
+    pub fn new(transport: T) -> Self {
+        Self(std::sync::Arc::new(transport))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncTransport for Blocking<T>
+where T: Transport + 'static {
+    fn send<'a>(&'a self, request: &'a Request) -> BoxFuture<'a, Result<Response, Error>> {
+        let transport = self.0.clone();
+        let request = request.clone();
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || transport.send(&request))
+                .await
+                .map_err(|e| Error::Transport(Box::new(e)))?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_retry_after() {
+        let response = Response {
+            status: 429,
+            headers: vec![("retry-after".to_string(), "7".to_string())],
+            body: br#"{"message":"Too many requests"}"#.to_vec(),
+        };
+
+        let err = response.error_for_status().expect_err("Should fail");
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+        assert_eq!(err.api_error().map(|e| e.message.as_str()), Some("Too many requests"));
+    }
+
+    #[test]
+    fn should_pass_successful_responses() {
+        let response = Response { status: 201, ..Response::default() };
+
+        assert!(response.error_for_status().is_ok());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn should_send_with_hyper() {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/member", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut buf = [0; 1024];
+            while !received.ends_with(b"{}") {
+                let n = stream.read(&mut buf).unwrap();
+                received.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 201 Created\r\nContent-Type: application/json\r\nContent-Length: 11\r\n\r\n{\"id\":1234}").unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let request = Request {
+            method: Method::Post,
+            url,
+            headers: vec![("Api-Key".to_string(), "secret".to_string())],
+            body: Some(b"{}".to_vec()),
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let response = runtime.block_on(HyperTransport::new().send(&request)).expect("Sent");
+
+        assert_eq!(response.status, 201);
+        assert_eq!(response.content_type(), Some("application/json"));
+        assert_eq!(response.body, br#"{"id":1234}"#);
+        let received = server.join().unwrap().to_lowercase();
+        assert!(received.starts_with("post /api/member http/1.1"));
+        assert!(received.contains("api-key: secret"));
+    }
+}
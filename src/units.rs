@@ -0,0 +1,228 @@
+use std::fmt;
+use std::str::FromStr;
+
+use num_traits::Float;
+use serde::{de, ser};
+
+use crate::Error;
+
+/// Enum describing the units a length can be measured in
+///
+/// The API expects centimetres, so lengths in any other unit are converted
+/// with [`LengthUnit::to_cm`] before being sent. Parsed leniently, ignoring
+/// case, surrounding whitespace and plurals.
+///
+/// # Examples
+///
+/// ```
+/// use transdirect::LengthUnit;
+///
+/// assert_eq!(LengthUnit::Millimetre.to_cm(455.0), 45.5);
+/// assert_eq!(LengthUnit::Inch.to_cm(10.0), 25.4);
+/// assert_eq!(" Inches ".parse::<LengthUnit>().unwrap(), LengthUnit::Inch);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum LengthUnit {
+    Millimetre,
+    #[default]
+    Centimetre,
+    Metre,
+    Inch,
+}
+
+impl LengthUnit {
+    /// The abbreviation of the unit
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Millimetre => "mm",
+            Self::Centimetre => "cm",
+            Self::Metre      => "m",
+            Self::Inch       => "in",
+        }
+    }
+
+    // How many centimetres are in one of this unit
+    fn cm(&self) -> f64 {
+        match self {
+            Self::Millimetre => 0.1,
+            Self::Centimetre => 1.0,
+            Self::Metre      => 100.0,
+            Self::Inch       => 2.54,
+        }
+    }
+
+    /// Converts a length in this unit to centimetres
+    pub fn to_cm<T>(&self, value: T) -> T where T: Float {
+        value * factor(self.cm())
+    }
+
+    /// Converts a length in centimetres to this unit
+    pub fn from_cm<T>(&self, value: T) -> T where T: Float {
+        value / factor(self.cm())
+    }
+
+    /// Converts a length in this unit to another unit
+    pub fn convert<T>(&self, value: T, to: LengthUnit) -> T where T: Float {
+        to.from_cm(self.to_cm(value))
+    }
+}
+
+impl FromStr for LengthUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mm" | "millimetre" | "millimetres" | "millimeter" | "millimeters" => Ok(Self::Millimetre),
+            "cm" | "centimetre" | "centimetres" | "centimeter" | "centimeters" => Ok(Self::Centimetre),
+            "m" | "metre" | "metres" | "meter" | "meters" => Ok(Self::Metre),
+            "in" | "inch" | "inches" | "\"" => Ok(Self::Inch),
+            other => Err(Error::Unrecognised(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for LengthUnit {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for LengthUnit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let variant = String::deserialize(deserializer)?;
+        variant.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Enum describing the units a weight can be measured in
+///
+/// The API expects kilograms, so weights in any other unit are converted
+/// with [`WeightUnit::to_kg`] before being sent. Parsed leniently, like
+/// [`LengthUnit`].
+///
+/// # Examples
+///
+/// ```
+/// use transdirect::WeightUnit;
+///
+/// assert_eq!(WeightUnit::Gram.to_kg(1250.0), 1.25);
+/// assert_eq!(WeightUnit::Kilogram.convert(1.0, WeightUnit::Pound), 1.0 / 0.45359237);
+/// assert_eq!("lbs".parse::<WeightUnit>().unwrap(), WeightUnit::Pound);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum WeightUnit {
+    Gram,
+    #[default]
+    Kilogram,
+    Pound,
+}
+
+impl WeightUnit {
+    /// The abbreviation of the unit
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gram     => "g",
+            Self::Kilogram => "kg",
+            Self::Pound    => "lb",
+        }
+    }
+
+    // How many kilograms are in one of this unit
+    fn kg(&self) -> f64 {
+        match self {
+            Self::Gram     => 0.001,
+            Self::Kilogram => 1.0,
+            Self::Pound    => 0.45359237, // Exactly, by international agreement
+        }
+    }
+
+    /// Converts a weight in this unit to kilograms
+    pub fn to_kg<T>(&self, value: T) -> T where T: Float {
+        value * factor(self.kg())
+    }
+
+    /// Converts a weight in kilograms to this unit
+    pub fn from_kg<T>(&self, value: T) -> T where T: Float {
+        value / factor(self.kg())
+    }
+
+    /// Converts a weight in this unit to another unit
+    pub fn convert<T>(&self, value: T, to: WeightUnit) -> T where T: Float {
+        to.from_kg(self.to_kg(value))
+    }
+}
+
+impl FromStr for WeightUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "g" | "gram" | "grams" | "gramme" | "grammes" => Ok(Self::Gram),
+            "kg" | "kgs" | "kilo" | "kilos" | "kilogram" | "kilograms" => Ok(Self::Kilogram),
+            "lb" | "lbs" | "pound" | "pounds" => Ok(Self::Pound),
+            other => Err(Error::Unrecognised(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for WeightUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ser::Serialize for WeightUnit {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ser::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for WeightUnit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: de::Deserializer<'de>
+    {
+        let variant = String::deserialize(deserializer)?;
+        variant.parse().map_err(de::Error::custom)
+    }
+}
+
+// Every float type can hold the conversion factors, if not exactly
+fn factor<T>(n: f64) -> T where T: Float {
+    T::from(n).expect("Conversion factor should be representable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_between_units() {
+        for unit in [LengthUnit::Millimetre, LengthUnit::Centimetre, LengthUnit::Metre, LengthUnit::Inch] {
+            let cm = unit.to_cm(12.5f64);
+            assert!((unit.from_cm(cm) - 12.5).abs() < 1e-12, "{unit}");
+            assert_eq!(unit.as_str().parse::<LengthUnit>().unwrap(), unit);
+        }
+        for unit in [WeightUnit::Gram, WeightUnit::Kilogram, WeightUnit::Pound] {
+            let kg = unit.to_kg(12.5f32);
+            assert!((unit.from_kg(kg) - 12.5).abs() < 1e-5, "{unit}");
+            assert_eq!(unit.as_str().parse::<WeightUnit>().unwrap(), unit);
+        }
+
+        assert!("furlong".parse::<LengthUnit>().is_err());
+        assert!(serde_json::from_str::<WeightUnit>(r#""stone""#).is_err());
+    }
+}
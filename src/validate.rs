@@ -0,0 +1,417 @@
+//! Checking requests before they are sent
+//!
+//! Transdirect rejects an invalid request one problem at a time, after a
+//! round trip. A [`Validator`] instead finds every problem with a
+//! [`BookingRequest`] or [`Order`] up front, each as a [`Problem`] naming
+//! the offending field, so that a form can highlight all of them at once.
+//!
+//! # Examples
+//!
+//! ```
+//! use transdirect::{Account, AustralianState, BookingRequest, Product};
+//! use transdirect::validate::{self, ProblemKind};
+//!
+//! let sender = Account { postcode: "2000".to_string(), state: AustralianState::Victoria, ..Account::default() };
+//! let request = BookingRequest { sender: Some(&sender), items: vec![Product::new()], ..BookingRequest::new() };
+//!
+//! let problems = validate::booking(&request);
+//! assert!(problems.iter().any(|p| p.field == "sender.state"));
+//! assert!(problems.iter().any(|p| p.field == "items[0].weight" && p.kind == ProblemKind::NotPositive));
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+
+use num_traits::{Float, Unsigned};
+use serde_derive::Serialize;
+use serde::ser;
+
+use crate::account::{Account, AustralianState};
+use crate::booking::BookingRequest;
+use crate::country::Country;
+use crate::courier::Courier;
+use crate::money::Amount;
+use crate::order::Order;
+use crate::product::Product;
+
+/// The most that can be declared unless [`Validator::max_declared_value`]
+/// says otherwise
+pub const DEFAULT_MAX_DECLARED_VALUE: f64 = 10_000.0;
+
+/// A single problem with a request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Problem {
+    /// The path to the field, e.g. `"receiver.postcode"` or `"items[2].weight"`
+    pub field: String,
+    #[serde(flatten)]
+    pub kind: ProblemKind,
+}
+
+impl Problem {
+    fn new(field: impl Into<String>, kind: ProblemKind) -> Self {
+        Self { field: field.into(), kind }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.kind)
+    }
+}
+
+/// What is wrong with a field
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum ProblemKind {
+    Missing,
+    NotPositive,
+    ZeroQuantity,
+    InvalidPostcode,
+    /// The postcode belongs to another state (`expected`, the one it is
+    /// allocated to), and is not one used across that state's border
+    PostcodeNotInState { expected: AustralianState },
+    InvalidCountry,
+    InvalidEmail,
+    TooValuable { max: f64 },
+    TooLong { courier: Courier, max_cm: f64 },
+    TooHeavy { courier: Courier, max_kg: f64 },
+}
+
+impl fmt::Display for ProblemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "is required"),
+            Self::NotPositive => write!(f, "must be greater than zero"),
+            Self::ZeroQuantity => write!(f, "must be at least one"),
+            Self::InvalidPostcode => write!(f, "is not a four digit postcode"),
+            Self::PostcodeNotInState { expected } => write!(f, "does not match the postcode, which is in {expected}"),
+            Self::InvalidCountry => write!(f, "is not a two-letter ISO country code"),
+            Self::InvalidEmail => write!(f, "is not an email address"),
+            Self::TooValuable { max } => write!(f, "must be between 0 and {max}"),
+            Self::TooLong { courier, max_cm } => write!(f, "is longer than {courier} accepts ({max_cm}cm)"),
+            Self::TooHeavy { courier, max_kg } => write!(f, "is heavier than {courier} accepts ({max_kg}kg)"),
+        }
+    }
+}
+
+/// The largest item a courier accepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CourierLimits {
+    pub max_length_cm: f64, // Of the longest side
+    pub max_weight_kg: f64,
+}
+
+impl CourierLimits {
+    pub fn new(max_length_cm: f64, max_weight_kg: f64) -> Self {
+        Self { max_length_cm, max_weight_kg }
+    }
+}
+
+/// Checks requests against configurable limits
+///
+/// Courier limits are only checked once a courier is known, either from
+/// [`Validator::courier`] or an order's `selected_courier`. The defaults are
+/// the couriers' published limits for single items at the time of writing,
+/// and can be replaced with [`Validator::courier_limits`].
+#[derive(Debug, Clone)]
+pub struct Validator {
+    max_declared_value: f64,
+    courier_limits: HashMap<Courier, CourierLimits>,
+    courier: Option<Courier>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        let courier_limits = [
+            (Courier::CouriersPleaseDomesticPriority, CourierLimits::new(120.0, 25.0)),
+            (Courier::Aramex, CourierLimits::new(120.0, 25.0)),
+            (Courier::Fastway, CourierLimits::new(120.0, 25.0)),
+            (Courier::Toll, CourierLimits::new(180.0, 35.0)),
+            (Courier::TollPriorityOvernight, CourierLimits::new(120.0, 25.0)),
+            (Courier::TollPrioritySameday, CourierLimits::new(120.0, 25.0)),
+            (Courier::HunterExpress, CourierLimits::new(180.0, 40.0)),
+            (Courier::StarTrack, CourierLimits::new(180.0, 70.0)),
+            (Courier::Tnt, CourierLimits::new(180.0, 70.0)),
+        ];
+
+        Self {
+            max_declared_value: DEFAULT_MAX_DECLARED_VALUE,
+            courier_limits: courier_limits.into_iter().collect(),
+            courier: None,
+        }
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn max_declared_value(mut self, max: f64) -> Self {
+        self.max_declared_value = max;
+        self
+    }
+
+    /// Sets the limits for a courier, replacing any default
+    pub fn courier_limits(mut self, courier: Courier, limits: CourierLimits) -> Self {
+        self.courier_limits.insert(courier, limits);
+        self
+    }
+
+    /// Checks items against the limits of the courier to be booked
+    pub fn courier(mut self, courier: Courier) -> Self {
+        self.courier = Some(courier);
+        self
+    }
+
+    /// Every problem with a booking request, which needs a sender, a
+    /// receiver and at least one item
+    pub fn booking<T, U, M>(&self, request: &BookingRequest<'_, T, U, M>) -> Vec<Problem>
+    where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+        let mut problems = Vec::new();
+
+        self.check_declared_value(request.declared_value, &mut problems);
+        match request.sender {
+            Some(sender) => check_account("sender", sender, &mut problems),
+            None => problems.push(Problem::new("sender", ProblemKind::Missing)),
+        }
+        match request.receiver {
+            Some(receiver) => check_account("receiver", receiver, &mut problems),
+            None => problems.push(Problem::new("receiver", ProblemKind::Missing)),
+        }
+        self.check_items(&request.items, self.courier.as_ref(), &mut problems);
+
+        problems
+    }
+
+    /// Every problem with an order, whose sender may be left to the member's
+    /// default
+    pub fn order<T, U, M>(&self, order: &Order<T, U, M>) -> Vec<Problem>
+    where T: Unsigned, U: Float, M: Amount {
+        let mut problems = Vec::new();
+
+        self.check_declared_value(order.declared_value, &mut problems);
+        if let Some(sender) = &order.sender {
+            check_account("sender", sender, &mut problems);
+        }
+        check_account("receiver", &order.receiver, &mut problems);
+        let courier = self.courier.as_ref().or(order.selected_courier.as_ref());
+        self.check_items(&order.items, courier, &mut problems);
+
+        problems
+    }
+
+    fn check_declared_value<M>(&self, value: M, problems: &mut Vec<Problem>)
+    where M: Amount {
+        let value = value.as_f64();
+        if !(0.0..=self.max_declared_value).contains(&value) {
+            problems.push(Problem::new("declared_value", ProblemKind::TooValuable { max: self.max_declared_value }));
+        }
+    }
+
+    fn check_items<T, U>(&self, items: &[Product<T, U>], courier: Option<&Courier>, problems: &mut Vec<Problem>)
+    where T: Unsigned, U: Float {
+        if items.is_empty() {
+            problems.push(Problem::new("items", ProblemKind::Missing));
+        }
+
+        let limits = courier.and_then(|c| Some((c, self.courier_limits.get(c)?)));
+        for (i, item) in items.iter().enumerate() {
+            if item.quantity.is_zero() {
+                problems.push(Problem::new(format!("items[{i}].quantity"), ProblemKind::ZeroQuantity));
+            }
+
+            let weight = item.weight.to_f64().unwrap_or(f64::NAN);
+            let sides = [
+                ("length", item.dimensions.length),
+                ("width", item.dimensions.width),
+                ("height", item.dimensions.height),
+            ].map(|(side, value)| (side, value.to_f64().unwrap_or(f64::NAN)));
+
+            if !is_positive(weight) {
+                problems.push(Problem::new(format!("items[{i}].weight"), ProblemKind::NotPositive));
+            }
+            for (side, value) in sides {
+                if !is_positive(value) {
+                    problems.push(Problem::new(format!("items[{i}].dimensions.{side}"), ProblemKind::NotPositive));
+                }
+            }
+
+            if let Some((courier, limits)) = limits {
+                if weight > limits.max_weight_kg {
+                    let kind = ProblemKind::TooHeavy { courier: courier.clone(), max_kg: limits.max_weight_kg };
+                    problems.push(Problem::new(format!("items[{i}].weight"), kind));
+                }
+                for (side, _) in sides.into_iter().filter(|(_, value)| *value > limits.max_length_cm) {
+                    let kind = ProblemKind::TooLong { courier: courier.clone(), max_cm: limits.max_length_cm };
+                    problems.push(Problem::new(format!("items[{i}].dimensions.{side}"), kind));
+                }
+            }
+        }
+    }
+}
+
+/// Shorthand for `Validator::new().booking(request)`
+pub fn booking<T, U, M>(request: &BookingRequest<'_, T, U, M>) -> Vec<Problem>
+where T: Unsigned + ser::Serialize, U: Float + ser::Serialize, M: Amount + ser::Serialize {
+    Validator::new().booking(request)
+}
+
+/// Shorthand for `Validator::new().order(order)`
+pub fn order<T, U, M>(order: &Order<T, U, M>) -> Vec<Problem>
+where T: Unsigned, U: Float, M: Amount {
+    Validator::new().order(order)
+}
+
+/// Every problem with a sender or receiver, with fields prefixed by `role`
+///
+/// Postcodes and states are only checked for Australian addresses, which
+/// must have a state; see [`AustralianState::postcode_states`].
+pub fn account(role: &str, account: &Account) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    check_account(role, account, &mut problems);
+    problems
+}
+
+fn check_account(role: &str, account: &Account, problems: &mut Vec<Problem>) {
+    if !account.country.is_iso() {
+        problems.push(Problem::new(format!("{role}.country"), ProblemKind::InvalidCountry));
+    }
+    if !account.email.is_empty() && !is_email(&account.email) {
+        problems.push(Problem::new(format!("{role}.email"), ProblemKind::InvalidEmail));
+    }
+
+    if account.country == Country::AU {
+        let unset = account.state == AustralianState::default();
+        if unset {
+            problems.push(Problem::new(format!("{role}.state"), ProblemKind::Missing));
+        }
+
+        let states = AustralianState::postcode_states(&account.postcode);
+        match states.first() {
+            None => problems.push(Problem::new(format!("{role}.postcode"), ProblemKind::InvalidPostcode)),
+            Some(expected) if !unset && !states.contains(&account.state) => {
+                problems.push(Problem::new(format!("{role}.state"), ProblemKind::PostcodeNotInState { expected: expected.clone() }));
+            },
+            Some(_) => {},
+        }
+    }
+}
+
+// NaN is never positive
+fn is_positive(value: f64) -> bool {
+    value > 0.0
+}
+
+// Deliberately loose; only the server can say whether an address is real
+fn is_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !email.chars().any(char::is_whitespace)
+        && !domain.contains('@')
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::product::Dimensions;
+
+    fn sydney() -> Account {
+        Account {
+            postcode: "2000".to_string(),
+            state: AustralianState::NewSouthWales,
+            email: "jo@example.com.au".to_string(),
+            ..Account::default()
+        }
+    }
+
+    fn parcel(weight: f64, longest: f64) -> Product<u32, f64> {
+        Product { quantity: 1, weight, dimensions: Dimensions::from_lwh(longest, 20.0, 10.0), ..Product::new() }
+    }
+
+    #[test]
+    fn should_accept_valid_booking() {
+        let account = sydney();
+        let request = BookingRequest::<u32, f64> {
+            declared_value: 100.0,
+            sender: Some(&account),
+            receiver: Some(&account),
+            items: vec![parcel(2.0, 30.0)],
+            ..BookingRequest::new()
+        };
+
+        assert_eq!(booking(&request), vec![]);
+    }
+
+    #[test]
+    fn should_report_every_problem() {
+        let receiver = Account { postcode: "3000".to_string(), country: Country::Other("AUS".to_string()), email: "jo@".to_string(), ..sydney() };
+        let request = BookingRequest::<u32, f64> {
+            declared_value: -1.0,
+            receiver: Some(&receiver),
+            items: vec![Product { quantity: 0, ..parcel(0.0, 30.0) }],
+            ..BookingRequest::new()
+        };
+
+        let fields: Vec<String> = booking(&request).into_iter().map(|p| p.field).collect();
+        assert_eq!(fields, [
+            "declared_value", "sender", "receiver.country", "receiver.email",
+            "items[0].quantity", "items[0].weight",
+        ]);
+    }
+
+    #[test]
+    fn should_check_postcode_against_state() {
+        let mut problems = Vec::new();
+        check_account("receiver", &Account { postcode: "2600".to_string(), ..sydney() }, &mut problems);
+        check_account("sender", &Account { postcode: "800".to_string(), ..sydney() }, &mut problems);
+        check_account("sender", &Account { postcode: "SW1A".to_string(), country: Country::GB, ..sydney() }, &mut problems);
+        check_account("sender", &Account { state: AustralianState::default(), ..sydney() }, &mut problems);
+
+        assert_eq!(problems, [
+            Problem::new("receiver.state", ProblemKind::PostcodeNotInState { expected: AustralianState::AustralianCapitalTerritory }),
+            Problem::new("sender.postcode", ProblemKind::InvalidPostcode),
+            Problem::new("sender.state", ProblemKind::Missing),
+        ]);
+    }
+
+    #[test]
+    fn should_accept_postcodes_used_across_borders() {
+        let mut problems = Vec::new();
+        for (postcode, state) in [("2620", AustralianState::AustralianCapitalTerritory), ("2620", AustralianState::NewSouthWales),
+            ("2540", AustralianState::AustralianCapitalTerritory), ("3644", AustralianState::NewSouthWales),
+            ("3707", AustralianState::NewSouthWales), ("4380", AustralianState::NewSouthWales)] {
+            check_account("receiver", &Account { postcode: postcode.to_string(), state, ..sydney() }, &mut problems);
+        }
+        assert_eq!(problems, []);
+
+        check_account("receiver", &Account { postcode: "3644".to_string(), state: AustralianState::Queensland, ..sydney() }, &mut problems);
+        assert_eq!(problems, [Problem::new("receiver.state", ProblemKind::PostcodeNotInState { expected: AustralianState::Victoria })]);
+    }
+
+    #[test]
+    fn should_check_courier_limits() {
+        let order = Order::<u32, f64> {
+            receiver: sydney(),
+            selected_courier: Some(Courier::CouriersPleaseDomesticPriority),
+            items: vec![parcel(30.0, 150.0), parcel(5.0, 50.0)],
+            ..Order::new()
+        };
+
+        let problems = order_problems(&order);
+        assert_eq!(problems, ["items[0].weight: is heavier than couriers_please_domestic_priority accepts (25kg)",
+            "items[0].dimensions.length: is longer than couriers_please_domestic_priority accepts (120cm)"]);
+
+        let problems = Validator::new().courier(Courier::StarTrack).order(&order);
+        assert!(problems.is_empty());
+    }
+
+    fn order_problems(order: &Order<u32, f64>) -> Vec<String> {
+        super::order(order).iter().map(Problem::to_string).collect()
+    }
+}
@@ -15,4 +15,156 @@ fn should_create_booking() {
     };
     
     assert!(b.items.len() == 1);
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_serialize_confirmation() {
+    let c = BookingConfirmation {
+        courier: transdirect::courier::Courier::Toll,
+        pickup_date: time::Date::from_calendar_date(2023, time::Month::March, 4).unwrap(),
+    };
+
+    assert_eq!(serde_json::to_string(&c).unwrap(), r#"{"courier":"toll","pickup-date":"2023-03-04"}"#);
+}
+
+#[test]
+fn should_deserialize_order() {
+    use transdirect::order::*;
+
+    let o: Order<u32, f64> = serde_json::from_str(r#"{
+        "id": 12, "transdirect_order_id": 3401, "order_id": "WEB-1042",
+        "goods_summary": "2x widgets", "sale_price": 40.0, "declared_value": 40.0,
+        "paid_price": 12.5, "items": [], "status": "manually_dispatched",
+        "receiver": { "address": "", "email": "", "name": "", "postcode": "2008", "state": "NSW",
+            "suburb": "Mosman", "type": "residential", "country": "AU", "company_name": "" }
+    }"#).unwrap();
+
+    assert_eq!(o.status, OrderStatus::ManuallyDispatched);
+    assert_eq!(o.transdirect_order_id, Some(3401));
+    assert!(serde_json::to_string(&o).unwrap().contains(r#""status":"manually_dispatched""#));
+
+    let o: Order<u32, f64> = serde_json::from_str(r#"{
+        "id": 13, "order_id": "WEB-1043", "goods_summary": "", "items": [], "status": "pending",
+        "receiver": { "address": "", "email": "", "name": "", "postcode": "2008", "state": "NSW",
+            "suburb": "Mosman", "type": "residential", "country": "AU", "company_name": "" }
+    }"#).unwrap();
+
+    assert_eq!((o.sale_price, o.declared_value, o.paid_price), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_convert_order_to_booking() {
+    use transdirect::order::*;
+
+    let item = Product { quantity: 2u32, weight: 1.5, ..Product::new() };
+    let o = Order { declared_value: 80.0f64, items: vec![item.clone()], ..Order::new() };
+    let b = BookingRequest::from(&o);
+
+    assert_eq!(b.items, vec![item]);
+    assert_eq!(b.receiver, Some(&o.receiver));
+    assert!(b.sender.is_none());
+}
+
+#[test]
+fn should_save_document() {
+    use transdirect::document::*;
+
+    let d = Document { kind: DocumentKind::Label, content_type: None, bytes: b"%PDF-1.4".to_vec() };
+    let path = std::env::temp_dir().join("transdirect-should-save-document.pdf");
+
+    assert!(d.is_pdf());
+    d.save(&path).unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"%PDF-1.4");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn should_pick_quotes_by_courier() {
+    use transdirect::courier::Courier;
+
+    let quotes: Quotes<f64> = serde_json::from_str(r#"{
+        "toll": { "total": 18.5, "price_insurance_ex": 16.0, "fee": 2.5, "insured_amount": 0.0,
+            "service": "road", "transit_time": "3-5 days", "pickup_dates": [], "pickup_time": {} },
+        "couriers_please_domestic_priority": { "total": 24.0, "price_insurance_ex": 21.5, "fee": 2.5,
+            "insured_amount": 0.0, "service": "Road", "transit_time": "1-2 days", "pickup_dates": [], "pickup_time": {} },
+        "someone_new": { "total": 30.0, "price_insurance_ex": 27.5, "fee": 2.5, "insured_amount": 0.0,
+            "service": "air", "transit_time": "Next day", "pickup_dates": [], "pickup_time": {} }
+    }"#).unwrap();
+
+    assert_eq!(quotes.cheapest().unwrap().0, &Courier::Toll);
+    assert_eq!(quotes.fastest().unwrap().0, &Courier::Other("someone_new".to_string()));
+    assert_eq!(quotes.with_service("road").count(), 2);
+    assert_eq!(quotes.for_courier(&Courier::CouriersPleaseDomesticPriority).unwrap().total, 24.0);
+
+    // Ties go the same way whatever order the quotes are held in
+    let tied = r#"{
+        "toll": { "total": 18.5, "price_insurance_ex": 16.0, "fee": 2.5, "insured_amount": 0.0,
+            "service": "road", "transit_time": "2 days", "pickup_dates": [], "pickup_time": {} },
+        "fastway": { "total": 18.5, "price_insurance_ex": 16.0, "fee": 2.5, "insured_amount": 0.0,
+            "service": "road", "transit_time": "2 days", "pickup_dates": [], "pickup_time": {} }
+    }"#;
+    let expected = std::cmp::min(Courier::Toll, Courier::Fastway);
+    for _ in 0..20 {
+        let quotes: Quotes<f64> = serde_json::from_str(tied).unwrap();
+        assert_eq!(quotes.cheapest().unwrap().0, &expected);
+        assert_eq!(quotes.fastest().unwrap().0, &expected);
+    }
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn should_quote_against_fake_server() {
+    use transdirect::testing::FakeTransdirect;
+
+    let fake = FakeTransdirect::new();
+    let c = fake.client();
+    let b = BookingRequest { declared_value: 20.0, items: vec![Product::new()], ..BookingRequest::new() };
+    let quote: transdirect::BookingResponse = c.quotes(&b).expect("Quoted");
+
+    assert!(quote.quotes.cheapest().is_some());
+    assert_eq!(fake.requests().len(), 1);
+}
+
+#[test]
+fn should_require_sender_receiver_and_items() {
+    let person = transdirect::Account::default();
+    let missing = BookingRequest::<u32, f64>::builder().receiver(&person).build();
+    assert!(matches!(missing, Err(transdirect::BuildError::Missing(ref fields)) if fields == &["sender", "items"]));
+
+    let b = BookingRequest::<u32, f64>::builder()
+        .sender(&person)
+        .receiver(&person)
+        .items(vec![Product::new(), Product::new()])
+        .build()
+        .expect("Complete request");
+    assert_eq!(b.items.len(), 2);
+    assert_eq!(b.referrer, DEFAULT_REFERRER);
+}
+
+#[test]
+fn should_normalise_account_fields() {
+    use transdirect::{Account, AccountKind, AustralianState, Country};
+
+    let a: Account = serde_json::from_str(r#"{ "address": "", "email": "", "name": "", "postcode": "6000",
+        "state": "Western Australia", "suburb": "Perth", "type": "Business", "country": "australia", "company_name": "" }"#)
+        .expect("Lenient account");
+    assert_eq!((&a.kind, &a.state, &a.country), (&AccountKind::Business, &AustralianState::WesternAustralia, &Country::AU));
+
+    let json = serde_json::to_value(&a).unwrap();
+    assert_eq!((&json["type"], &json["state"], &json["country"]), (&"business".into(), &"WA".into(), &"AU".into()));
+
+    let foreign: Account = serde_json::from_str(r#"{ "address": "", "email": "", "name": "", "postcode": "90210",
+        "state": "CA", "suburb": "", "type": "po_box", "country": "US", "company_name": "" }"#).unwrap();
+    assert_eq!(foreign.state, AustralianState::Other("CA".to_string()));
+    assert_eq!(foreign.kind, AccountKind::Other("po_box".to_string()));
+    assert!("po_box".parse::<AccountKind>().is_err());
+}
+
+#[test]
+fn should_read_real_member_ids() {
+    let m: transdirect::Member = serde_json::from_str(r#"{ "id": 184467, "company_name": "Top End Traders",
+        "postcode": 800, "active": true, "balance": 12.5 }"#).expect("Member");
+
+    assert_eq!((m.id, m.postcode.as_str(), m.balance), (184467, "0800", 12.5));
+    assert!(m.default_sender.is_none());
+}